@@ -0,0 +1,98 @@
+import test from "node:test";
+import assert from "node:assert";
+import {
+  BackendName,
+  ErrorCode,
+  FontDialog,
+  capabilities,
+  getBackend,
+  mock,
+  setBackend,
+} from "./index.js";
+
+const native = {
+  win32: BackendName.Windows,
+  darwin: BackendName.MacOs,
+}[process.platform];
+
+test.afterEach(() => {
+  setBackend(null);
+  delete process.env.RFD_BACKEND;
+});
+
+test("getBackend() defaults to the native dialogs", () => {
+  const backend = getBackend();
+  if (native) {
+    assert.equal(backend, native);
+  } else {
+    assert.ok([BackendName.Gtk, BackendName.Portal].includes(backend));
+  }
+});
+
+test("getBackend() follows RFD_BACKEND, setBackend() and mock", () => {
+  process.env.RFD_BACKEND = "kdialog";
+  assert.equal(getBackend(), "kdialog");
+  setBackend("zenity");
+  assert.equal(getBackend(), "zenity");
+  mock.install();
+  assert.equal(getBackend(), "mock");
+  mock.uninstall();
+  assert.equal(getBackend(), "kdialog");
+});
+
+test("setBackend() rejects other platforms' native dialogs", () => {
+  const other = native === BackendName.Windows ? "macos" : "windows";
+  assert.throws(() => setBackend(other), {
+    code: ErrorCode.BackendUnavailable,
+  });
+  assert.notEqual(getBackend(), other);
+});
+
+test("capabilities() describes the current backend", () => {
+  setBackend("mock");
+  assert.deepEqual(capabilities(), {
+    backend: "mock",
+    fileDialog: {
+      filters: true,
+      directory: true,
+      fileName: true,
+      title: true,
+      parent: true,
+      pickFiles: true,
+      pickFolders: true,
+      saveFiles: true,
+    },
+    messageDialog: {
+      title: true,
+      level: true,
+      parent: true,
+      customButtons: true,
+      timeout: true,
+    },
+    inputDialog: { available: true },
+    colorDialog: { available: true, alpha: true },
+    fontDialog: { available: true },
+    notification: { available: true },
+  });
+});
+
+test("capabilities() can describe another backend", () => {
+  const { backend, fileDialog, fontDialog } = capabilities("kdialog");
+  assert.equal(backend, "kdialog");
+  assert.equal(fileDialog.pickFolders, false);
+  assert.equal(fileDialog.filters, true);
+  assert.equal(fontDialog.available, false);
+  assert.equal(getBackend() === "kdialog", false);
+});
+
+test("font dialogs throw outside of the mock backend", () => {
+  setBackend("zenity");
+  assert.throws(() => new FontDialog().show(), {
+    code: ErrorCode.BackendUnavailable,
+  });
+  setBackend(null);
+  assert.equal(capabilities().fontDialog.available, false);
+  assert.throws(() => new FontDialog().show(), {
+    code: ErrorCode.BackendUnavailable,
+  });
+});
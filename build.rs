@@ -1,5 +1,19 @@
 extern crate napi_build;
 
+use std::env;
+
 fn main() {
     napi_build::setup();
+
+    // `#[cfg(freedesktop)]` stands for Linux and the BSDs, where the dialogs
+    // come from GTK, the XDG desktop portal, `zenity` or `kdialog`. The target
+    // table in Cargo.toml can't use it, so keep the list there in sync.
+    println!("cargo:rustc-check-cfg=cfg(freedesktop)");
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if matches!(
+        os.as_str(),
+        "linux" | "freebsd" | "dragonfly" | "netbsd" | "openbsd"
+    ) {
+        println!("cargo:rustc-cfg=freedesktop");
+    }
 }
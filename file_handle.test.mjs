@@ -0,0 +1,403 @@
+import test from "node:test";
+import assert from "node:assert";
+import { execFileSync } from "node:child_process";
+import { createHash } from "node:crypto";
+import {
+  chmod,
+  mkdir,
+  mkdtemp,
+  open,
+  readFile,
+  readdir,
+  rename,
+  rm,
+  stat,
+  writeFile,
+} from "node:fs/promises";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { ErrorCode, FileHandle } from "./index.js";
+
+// Creates an empty folder for the test `t` that is removed once it's done,
+// even if it fails.
+async function tempDir(t) {
+  const dir = await mkdtemp(join(tmpdir(), "rfd-"));
+  t.after(() => rm(dir, { recursive: true, force: true }));
+  return dir;
+}
+
+test("FileHandle.write() replaces the file", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "hello.txt"));
+  await fileHandle.write(Buffer.from("Hello"));
+  await fileHandle.write(Buffer.from("Hello, world!"));
+  assert.equal(await readFile(fileHandle.path(), "utf8"), "Hello, world!");
+});
+
+test("FileHandle.append() adds to the end of the file", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "log.txt"));
+  await fileHandle.append(Buffer.from("a\n"));
+  await fileHandle.append(Buffer.from("b\n"));
+  assert.equal(await readFile(fileHandle.path(), "utf8"), "a\nb\n");
+});
+
+test("FileHandle.writeAtomic() leaves no temporary file behind", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "settings.json"));
+  await fileHandle.write(Buffer.from("{}"));
+  await fileHandle.writeAtomic(Buffer.from('{"a":1}'));
+  assert.equal(await readFile(fileHandle.path(), "utf8"), '{"a":1}');
+  assert.deepEqual(await readdir(dir), ["settings.json"]);
+});
+
+test("FileHandle.writeAtomic() keeps concurrent writes whole", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "settings.json"));
+  await fileHandle.write(Buffer.from("{}"));
+  await chmod(fileHandle.path(), 0o600);
+  const writes = [];
+  for (let i = 0; i < 20; i++) {
+    const fill = String.fromCharCode(97 + i);
+    writes.push(fileHandle.writeAtomic(Buffer.alloc(64 * 1024, fill)));
+  }
+  await Promise.all(writes);
+  const text = await readFile(fileHandle.path(), "utf8");
+  assert.equal(text, text[0].repeat(64 * 1024));
+  assert.deepEqual(await readdir(dir), ["settings.json"]);
+  if (process.platform !== "win32") {
+    assert.equal((await stat(fileHandle.path())).mode & 0o777, 0o600);
+  }
+});
+
+test("FileHandle.stream() reads the file in chunks", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "data.bin"));
+  await fileHandle.write(Buffer.from("0123456789"));
+  const chunks = [];
+  for await (const chunk of fileHandle.stream({ chunkSize: 4, start: 1, end: 9 })) {
+    chunks.push(chunk.toString());
+  }
+  assert.deepEqual(chunks, ["1234", "5678"]);
+});
+
+test("FileHandle.stream() stops after cancel()", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "data.bin"));
+  await fileHandle.write(Buffer.from("0123456789"));
+  const stream = fileHandle.stream({ chunkSize: 2 });
+  assert.equal((await stream.next()).value.toString(), "01");
+  stream.cancel();
+  assert.deepEqual(await stream.next(), { done: true });
+});
+
+test(
+  "FileHandle.stream() cancel() doesn't wait for a pending read",
+  { skip: process.platform === "win32" },
+  async (t) => {
+    const dir = await tempDir(t);
+    const fifo = join(dir, "fifo");
+    execFileSync("mkfifo", [fifo]);
+    const stream = FileHandle.wrap(fifo).stream();
+    // Opening a FIFO blocks until there's a writer.
+    const next = stream.next();
+    await new Promise((resolve) => setTimeout(resolve, 50));
+    stream.cancel();
+    const writer = await open(fifo, "w");
+    await writer.close();
+    assert.deepEqual(await next, { done: true });
+    assert.deepEqual(await stream.next(), { done: true });
+  },
+);
+
+test("FileHandle I/O errors have a code, errno, syscall and path", async (t) => {
+  const dir = await tempDir(t);
+  const path = join(dir, "missing", "hello.txt");
+  await assert.rejects(FileHandle.wrap(path).write(Buffer.from("Hi")), {
+    code: ErrorCode.Io,
+    syscall: "open",
+    path,
+  });
+});
+
+test("FileHandle.read() rejects with the real I/O error", async (t) => {
+  const dir = await tempDir(t);
+  const path = join(dir, "missing.txt");
+  await assert.rejects(FileHandle.wrap(path).read(), {
+    code: ErrorCode.Io,
+    kind: "NotFound",
+    syscall: "open",
+    path,
+  });
+  await assert.rejects(FileHandle.wrap(dir).read(), {
+    code: ErrorCode.Io,
+    path: dir,
+  });
+});
+
+test("FileHandle.stat() matches fs.stat()", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "hello.txt"));
+  await fileHandle.write(Buffer.from("Hello, world!"));
+  const expected = await stat(fileHandle.path());
+  const actual = await fileHandle.stat();
+  assert.equal(actual.size, 13);
+  assert.equal(actual.type, "file");
+  assert.equal(actual.mode, expected.mode);
+  assert.equal(Math.floor(actual.mtimeMs), Math.floor(expected.mtimeMs));
+  if (process.platform !== "win32") {
+    assert.equal(actual.uid, expected.uid);
+    assert.equal(Math.floor(actual.ctimeMs), Math.floor(expected.ctimeMs));
+  }
+  assert.equal((await FileHandle.wrap(dir).stat()).type, "directory");
+  await assert.rejects(FileHandle.wrap(join(dir, "missing")).stat(), {
+    code: ErrorCode.Io,
+    syscall: "stat",
+  });
+});
+
+test("FileHandle.mimeType() sniffs the content before the extension", async (t) => {
+  const dir = await tempDir(t);
+  const files = {
+    "image.txt": Buffer.from("89504e470d0a1a0a0000000d49484452", "hex"),
+    "data.json": Buffer.from('{"a":1}'),
+    notes: Buffer.from("Hello, world!"),
+    blob: Buffer.from([0, 1, 2, 3]),
+    "report.docx": Buffer.from("PK\x03\x04"),
+    "archive.bin": Buffer.from("PK\x03\x04"),
+  };
+  const mimeTypes = {};
+  for (const [name, bytes] of Object.entries(files)) {
+    const fileHandle = FileHandle.wrap(join(dir, name));
+    await fileHandle.write(bytes);
+    mimeTypes[name] = await fileHandle.mimeType();
+  }
+  assert.deepEqual(mimeTypes, {
+    "image.txt": "image/png",
+    "data.json": "application/json",
+    notes: "text/plain",
+    blob: "application/octet-stream",
+    "report.docx":
+      "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "archive.bin": "application/zip",
+  });
+  assert.equal(await FileHandle.wrap(dir).mimeType(), "inode/directory");
+});
+
+test("FileHandle.readRange() reads only the requested bytes", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "data.bin"));
+  await fileHandle.write(Buffer.from("0123456789"));
+  assert.equal((await fileHandle.readRange(2, 3)).toString(), "234");
+  assert.equal((await fileHandle.readRange(8, 100)).toString(), "89");
+  assert.equal((await fileHandle.readRange(20, 4)).length, 0);
+  await assert.rejects(fileHandle.readRange(-1, 4), {
+    code: ErrorCode.InvalidArg,
+  });
+});
+
+test("FileHandle.open() keeps the file open for reads and seeks", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "data.bin"));
+  await fileHandle.write(Buffer.from("0123456789"));
+  const cursor = await fileHandle.open();
+  assert.equal((await cursor.read(3)).toString(), "012");
+  assert.equal((await cursor.read(3)).toString(), "345");
+  assert.equal(await cursor.seek(8), 8);
+  assert.equal((await cursor.read(3)).toString(), "89");
+  assert.equal((await cursor.read(3)).length, 0);
+  if (process.platform !== "win32") {
+    // The open file keeps working even after it's deleted.
+    await rm(fileHandle.path());
+  }
+  await cursor.seek(1);
+  assert.equal((await cursor.read(2)).toString(), "12");
+  cursor.close();
+  cursor.close();
+  await assert.rejects(cursor.read(1), { code: ErrorCode.InvalidArg });
+  await assert.rejects(FileHandle.wrap(join(dir, "missing")).open(), {
+    code: ErrorCode.Io,
+    syscall: "open",
+  });
+});
+
+test("FileHandle.text() detects the byte order mark", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "text.txt"));
+  await fileHandle.write(Buffer.from("﻿héllo"));
+  assert.equal(await fileHandle.text(), "héllo");
+  await fileHandle.write(Buffer.from("﻿héllo 🌍", "utf16le"));
+  assert.equal(await fileHandle.text(), "héllo 🌍");
+  await fileHandle.write(Buffer.from("﻿héllo", "utf16le").swap16());
+  assert.equal(await fileHandle.text(), "héllo");
+  await fileHandle.write(Buffer.from("héllo", "latin1"));
+  assert.equal(await fileHandle.text({ encoding: "latin1" }), "héllo");
+  assert.equal(await fileHandle.text(), "h�llo");
+});
+
+test("FileHandle.json() reports the line and column of mistakes", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "config.json"));
+  await fileHandle.write(Buffer.from('﻿{ "a": [1, 2.5e3, "ü"], "b": null }'));
+  assert.deepEqual(await fileHandle.json(), { a: [1, 2500, "ü"], b: null });
+  await fileHandle.write(Buffer.from('{\n  "ü": "ö",\n  "b": tru\n}'));
+  await assert.rejects(fileHandle.json(), {
+    code: ErrorCode.InvalidData,
+    path: fileHandle.path(),
+    line: 3,
+    column: 11,
+  });
+  await fileHandle.write(Buffer.from("[1, 2,]"));
+  await assert.rejects(fileHandle.json(), { line: 1, column: 7 });
+  await fileHandle.write(Buffer.from('{"ü": x}'));
+  await assert.rejects(fileHandle.json(), { line: 1, column: 7 });
+  await fileHandle.write(Buffer.from("[".repeat(100000)));
+  await assert.rejects(fileHandle.json(), { line: 1, column: 128 });
+  await fileHandle.write(Buffer.from('{"b": 1, "a": 9007199254740993}'));
+  const value = await fileHandle.json();
+  assert.deepEqual(Object.keys(value), ["b", "a"]);
+  assert.equal(value.a, 9007199254740993);
+});
+
+test("FileHandle.lines() yields each line without its line ending", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "log.txt"));
+  await fileHandle.write(Buffer.from("a,b\r\n\nc\nlast"));
+  const lines = [];
+  for await (const line of fileHandle.lines()) {
+    lines.push(line);
+  }
+  assert.deepEqual(lines, ["a,b", "", "c", "last"]);
+  await fileHandle.write(Buffer.from("﻿é\r\n🌍\n", "utf16le"));
+  lines.length = 0;
+  for await (const line of fileHandle.lines()) {
+    lines.push(line);
+  }
+  assert.deepEqual(lines, ["é", "🌍"]);
+  const stream = fileHandle.lines();
+  await stream.next();
+  stream.cancel();
+  assert.deepEqual(await stream.next(), { done: true });
+});
+
+test("FileHandle.hash() matches node:crypto and reports progress", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "data.bin"));
+  const data = Buffer.alloc(3 * 1024 * 1024 + 5, "rfd");
+  await fileHandle.write(data);
+  for (const algorithm of ["sha256", "sha1", "md5"]) {
+    const digest = createHash(algorithm).update(data).digest("hex");
+    assert.equal(await fileHandle.hash(algorithm), digest);
+  }
+  const progress = [];
+  await fileHandle.hash("sha256", ({ bytesHashed, totalBytes }) => {
+    assert.equal(totalBytes, data.length);
+    progress.push(bytesHashed / 1024 / 1024);
+  });
+  assert.deepEqual(progress, [1, 2, 3, data.length / 1024 / 1024]);
+  await fileHandle.write(Buffer.alloc(0));
+  assert.equal(
+    await fileHandle.hash("blake3"),
+    "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+  );
+  await assert.rejects(FileHandle.wrap(join(dir, "missing")).hash("md5"), {
+    code: ErrorCode.Io,
+    syscall: "open",
+  });
+});
+
+test("FileHandle.hash() rejects with what onProgress throws", async (t) => {
+  const dir = await tempDir(t);
+  const fileHandle = FileHandle.wrap(join(dir, "data.bin"));
+  await fileHandle.write(Buffer.alloc(4 * 1024 * 1024 + 1));
+  const error = new Error("stop");
+  let calls = 0;
+  const promise = fileHandle.hash("sha256", () => {
+    calls++;
+    throw error;
+  });
+  await assert.rejects(promise, (x) => x === error);
+  assert.equal(calls, 1);
+  assert.match(await fileHandle.hash("md5"), /^[0-9a-f]{32}$/);
+});
+
+test("FileHandle.watch() reports changes until close()", async (t) => {
+  const dir = await tempDir(t);
+  const watcher = FileHandle.wrap(dir).watch({ recursive: true });
+  // Skips events until one matches, since platforms may report extra ones
+  // like a `modify` of the folder itself.
+  async function expectEvent(type, path) {
+    while (true) {
+      const { done, value } = await watcher.next();
+      assert.equal(done, false);
+      if (value.type === type && value.fileHandle.path() === path) {
+        return value;
+      }
+    }
+  }
+  await mkdir(join(dir, "sub"));
+  await expectEvent("create", join(dir, "sub"));
+  await writeFile(join(dir, "sub", "a.txt"), "a");
+  await expectEvent("create", join(dir, "sub", "a.txt"));
+  await expectEvent("modify", join(dir, "sub", "a.txt"));
+  await rename(join(dir, "sub", "a.txt"), join(dir, "b.txt"));
+  const event = await expectEvent("rename", join(dir, "sub", "a.txt"));
+  if (process.platform !== "darwin") {
+    assert.equal(event.newFileHandle.path(), join(dir, "b.txt"));
+  }
+  await rm(join(dir, "b.txt"));
+  await expectEvent("remove", join(dir, "b.txt"));
+  const next = watcher.next();
+  watcher.close();
+  assert.deepEqual(await next, { done: true });
+  assert.deepEqual(await watcher.next(), { done: true });
+  assert.throws(() => FileHandle.wrap(join(dir, "missing")).watch(), {
+    code: ErrorCode.Io,
+    syscall: "watch",
+  });
+});
+
+test(
+  "FileCursor.close() doesn't wait for a pending read",
+  { skip: process.platform === "win32" },
+  async (t) => {
+    const dir = await tempDir(t);
+    const fifo = join(dir, "fifo");
+    execFileSync("mkfifo", [fifo]);
+    const [cursor, writer] = await Promise.all([
+      FileHandle.wrap(fifo).open(),
+      open(fifo, "w"),
+    ]);
+    // Reading a FIFO blocks until there's data or the writer closes it.
+    // Handled before the writer is closed, which may settle it right away.
+    const read = assert.rejects(cursor.read(10), {
+      code: ErrorCode.InvalidArg,
+    });
+    await new Promise((resolve) => setTimeout(resolve, 50));
+    cursor.close();
+    await writer.close();
+    await read;
+    await assert.rejects(cursor.read(1), { code: ErrorCode.InvalidArg });
+  },
+);
+
+test(
+  "FileHandle.lines() cancel() doesn't wait for a pending read",
+  { skip: process.platform === "win32" },
+  async (t) => {
+    const dir = await tempDir(t);
+    const fifo = join(dir, "fifo");
+    execFileSync("mkfifo", [fifo]);
+    const lines = FileHandle.wrap(fifo).lines();
+    const first = lines.next();
+    const writer = await open(fifo, "w");
+    await writer.write("a\n");
+    assert.deepEqual(await first, { done: false, value: "a" });
+    // Blocks until there's another line or the writer closes the FIFO.
+    const next = lines.next();
+    await new Promise((resolve) => setTimeout(resolve, 50));
+    lines.cancel();
+    await writer.close();
+    assert.deepEqual(await next, { done: true });
+  },
+);
@@ -0,0 +1,76 @@
+// The entry point. `binding.js` is generated by `napi build` and loads the
+// `.node` addon for the current platform. On Linux, the package also ships a
+// build without GTK (`--no-default-features --features xdg-portal`) that's used
+// instead when the GTK one can't be loaded, like when libgtk-3 isn't installed.
+// On musl, there is only that build.
+const { execSync } = require("node:child_process");
+const { existsSync, readFileSync } = require("node:fs");
+const Module = require("node:module");
+const { join } = require("node:path");
+
+// The same check that `binding.js` uses to pick the addon: only glibc reports
+// its version.
+function isMusl() {
+  if (typeof process.report?.getReport !== "function") {
+    try {
+      const ldd = execSync("which ldd").toString().trim();
+      return readFileSync(ldd, "utf8").includes("musl");
+    } catch {
+      return true;
+    }
+  }
+  return !process.report.getReport().header.glibcVersionRuntime;
+}
+
+// Whether the addon couldn't be loaded because a shared library that it links
+// to, like libgtk-3.so.0, isn't installed. Other errors aren't fixed by
+// loading the GTK-free build, so they're thrown as they are.
+function isMissingLibrary(error) {
+  const message = `${error?.message}`;
+  return /cannot open shared object file|Error loading shared library/.test(
+    message,
+  );
+}
+
+if (process.platform === "linux") {
+  const abi = isMusl() ? "musl" : "gnu";
+  const gtk = join(__dirname, `index.linux-${process.arch}-${abi}.node`);
+  const portal = join(
+    __dirname,
+    `index.linux-${process.arch}-${abi}.xdg-portal.node`,
+  );
+  let gtkError = null;
+  if (existsSync(gtk) && existsSync(portal)) {
+    try {
+      require(gtk);
+    } catch (error) {
+      if (!isMissingLibrary(error)) {
+        throw error;
+      }
+      gtkError = error;
+    }
+  }
+  if (existsSync(portal) && (gtkError || !existsSync(gtk))) {
+    let exports;
+    try {
+      exports = require(portal);
+    } catch (error) {
+      if (gtkError) {
+        error.cause = gtkError;
+      }
+      throw error;
+    }
+    // `binding.js` requires the GTK build by its path, or a package that
+    // doesn't exist when there is no GTK build, so it's swapped for the
+    // GTK-free one.
+    const binding = join(__dirname, "binding.js");
+    const bindingModule = new Module(binding, module);
+    bindingModule.filename = binding;
+    bindingModule.loaded = true;
+    bindingModule.exports = exports;
+    require.cache[binding] = bindingModule;
+  }
+}
+
+// A plain re-export so that Node.js can find the named exports for `import`.
+module.exports = require("./binding.js");
@@ -8,6 +8,7 @@ import {
   MessageButtons,
   MessageDialog,
   MessageLevel,
+  mock,
 } from "./index.js";
 
 // Exposed for 'npm run test:repl' to play with!
@@ -18,3 +19,4 @@ globalThis.FileHandle = FileHandle;
 globalThis.MessageButtons = MessageButtons;
 globalThis.MessageDialog = MessageDialog;
 globalThis.MessageLevel = MessageLevel;
+globalThis.mock = mock;
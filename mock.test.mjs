@@ -0,0 +1,317 @@
+import test from "node:test";
+import assert from "node:assert";
+import { getEventListeners } from "node:events";
+import {
+  AsyncColorDialog,
+  AsyncFileDialog,
+  AsyncFontDialog,
+  AsyncInputDialog,
+  AsyncMessageDialog,
+  ErrorCode,
+  FileDialog,
+  FileHandle,
+  FontDialog,
+  InputDialog,
+  MessageDialog,
+  MessageDialogResult,
+  Notification,
+  message,
+  mock,
+  openFile,
+  openFiles,
+  saveFile,
+} from "./index.js";
+
+test.beforeEach(() => {
+  mock.install();
+});
+
+test.afterEach(() => {
+  mock.uninstall();
+});
+
+test("FileDialog.pickFile() returns the queued path", () => {
+  mock.pushPaths(["/tmp/hello.txt"]);
+  const path = new FileDialog()
+    .addFilter("Text", ["txt"])
+    .setDirectory("/tmp")
+    .setTitle("Open")
+    .pickFile();
+  assert.equal(path, "/tmp/hello.txt");
+  assert.deepEqual(mock.requests(), [
+    {
+      method: "pickFile",
+      title: "Open",
+      directory: "/tmp",
+      filters: [{ name: "Text", extensions: ["txt"] }],
+    },
+  ]);
+});
+
+test("AsyncFileDialog.pickFiles() returns FileHandles", async () => {
+  mock.pushPaths(["/tmp/a.txt", "/tmp/b.txt"]);
+  const fileHandles = await new AsyncFileDialog().pickFiles();
+  assert.deepEqual(
+    fileHandles.map((x) => x.path()),
+    ["/tmp/a.txt", "/tmp/b.txt"],
+  );
+});
+
+test("AsyncFileDialog.saveFile() records the file name", async () => {
+  mock.pushCancel();
+  const fileHandle = await new AsyncFileDialog()
+    .setFileName("Document.txt")
+    .saveFile();
+  assert.equal(fileHandle, null);
+  assert.equal(mock.requests()[0].method, "saveFile");
+  assert.equal(mock.requests()[0].fileName, "Document.txt");
+});
+
+test("FileDialog.saveFiles() records the file names", () => {
+  mock.pushPaths(["/tmp/a.txt", "/tmp/b.txt"]);
+  const paths = new FileDialog().saveFiles(["a.txt", "b.txt"]);
+  assert.deepEqual(paths, ["/tmp/a.txt", "/tmp/b.txt"]);
+  assert.equal(mock.requests()[0].method, "saveFiles");
+  assert.deepEqual(mock.requests()[0].fileNames, ["a.txt", "b.txt"]);
+});
+
+test(
+  "FileDialog.setPathEncoding('Buffer') keeps non-UTF-8 paths",
+  { skip: process.platform === "win32" },
+  () => {
+    const bytes = Buffer.from([0x2f, 0x74, 0x6d, 0x70, 0x2f, 0xe9]);
+    mock.pushPaths([bytes]);
+    assert.throws(() => new FileDialog().pickFile(), {
+      code: ErrorCode.NonUtf8Path,
+      path: "/tmp/\ufffd",
+    });
+    mock.pushPaths([bytes]);
+    const path = new FileDialog().setPathEncoding("Buffer").pickFile();
+    assert.deepEqual(path, bytes);
+    assert.deepEqual(FileHandle.wrap(path).pathBuffer(), bytes);
+  },
+);
+
+test("MessageDialog.show() returns the queued button", async () => {
+  mock.pushButton(true);
+  mock.pushButton(false);
+  assert.equal(new MessageDialog().setButtons("OkCancel").show(), "Ok");
+  assert.equal(
+    await new AsyncMessageDialog().setLevel("Error").setTitle("Uh oh!").show(),
+    "Cancel",
+  );
+  assert.equal(mock.requests()[0].buttons, "OkCancel");
+  assert.equal(mock.requests()[1].level, "Error");
+});
+
+test("MessageDialog.setButtons() supports custom labels", async () => {
+  const labels = ["Save", "Don't Save", "Cancel"];
+  mock.pushButton(true);
+  mock.pushButton("Don't Save");
+  mock.pushCancel();
+  const dialog = new AsyncMessageDialog().setButtons(
+    "YesNoCancelCustom",
+    labels,
+  );
+  assert.equal(await dialog.show(), "Save");
+  assert.equal(await dialog.show(), "Don't Save");
+  assert.equal(await dialog.show(), MessageDialogResult.Cancel);
+  assert.deepEqual(mock.requests()[0].labels, labels);
+  assert.throws(() => new MessageDialog().setButtons("OkCustom", []), {
+    code: ErrorCode.InvalidArg,
+  });
+});
+
+test("dialogs throw when no answer is queued", async () => {
+  assert.throws(() => new FileDialog().pickFile(), {
+    code: ErrorCode.BackendUnavailable,
+  });
+  await assert.rejects(new AsyncMessageDialog().show(), {
+    code: "ERR_BACKEND_UNAVAILABLE",
+  });
+});
+
+test("InputDialog.show() returns the queued text", async () => {
+  mock.pushText("Photos");
+  mock.pushCancel();
+  const name = await new AsyncInputDialog()
+    .setTitle("New folder")
+    .setDefaultValue("Untitled")
+    .show();
+  assert.equal(name, "Photos");
+  assert.equal(new InputDialog().setPassword(true).show(), null);
+  assert.deepEqual(mock.requests(), [
+    {
+      method: "show",
+      title: "New folder",
+      defaultValue: "Untitled",
+      password: false,
+      multiline: false,
+    },
+    { method: "show", password: true, multiline: false },
+  ]);
+});
+
+test("ColorDialog.show() returns the queued color", async () => {
+  mock.pushColor("#ff880080");
+  mock.pushColor("#ff880080");
+  const dialog = new AsyncColorDialog().setColor("#123");
+  assert.deepEqual(await dialog.show(), {
+    r: 255,
+    g: 136,
+    b: 0,
+    a: 1,
+    hex: "#ff8800",
+  });
+  const color = await dialog.clone().setAlpha(true).show();
+  assert.equal(color.hex, "#ff880080");
+  assert.equal(mock.requests()[0].color, "#112233ff");
+  assert.equal(mock.requests()[1].alpha, true);
+  assert.throws(() => mock.pushColor("orange"), {
+    code: ErrorCode.InvalidArg,
+  });
+});
+
+test("FontDialog.show() returns the queued font", async () => {
+  const font = { family: "Fira Code", style: "italic", weight: 700, size: 11 };
+  mock.pushFont(font);
+  mock.pushCancel();
+  const dialog = new FontDialog().setFamily("Inter").setSize(12).setWeight(400);
+  assert.deepEqual(dialog.show(), font);
+  assert.equal(await new AsyncFontDialog().setStyle("oblique").show(), null);
+  assert.equal(mock.requests()[0].family, "Inter");
+  assert.equal(mock.requests()[0].size, 12);
+  assert.equal(mock.requests()[1].style, "oblique");
+  assert.throws(() => dialog.setWeight(1000), { code: ErrorCode.InvalidArg });
+  assert.throws(() => mock.pushFont({ ...font, size: 0 }), {
+    code: ErrorCode.InvalidArg,
+  });
+});
+
+test("Notification.show() returns the queued action", async () => {
+  mock.pushButton("open");
+  mock.pushCancel();
+  const notification = new Notification()
+    .setTitle("Download finished")
+    .setUrgency("Warning")
+    .addAction("open", "Open");
+  assert.equal(await notification.show(), "open");
+  assert.equal(await notification.clone().setTimeout(0).show(), null);
+  const [request, cloned] = mock.requests();
+  assert.equal(request.title, "Download finished");
+  assert.equal(request.level, "Warning");
+  assert.deepEqual(request.actions, [{ id: "open", label: "Open" }]);
+  assert.equal(cloned.timeout, 0);
+});
+
+test("a timed out dialog returns its default result", async () => {
+  mock.pushTimeout();
+  mock.pushTimeout();
+  const dialog = new AsyncMessageDialog()
+    .setButtons("YesNo")
+    .setTimeout(5000, "Yes");
+  assert.deepEqual(await dialog.showDetailed(), {
+    result: "Yes",
+    timedOut: true,
+  });
+  assert.equal(await dialog.show(), "Yes");
+  assert.equal(mock.requests()[0].timeout, 5000);
+  mock.pushButton(false);
+  assert.deepEqual(new MessageDialog().setTimeout(5000, "Ok").showDetailed(), {
+    result: "Cancel",
+    timedOut: false,
+  });
+  mock.pushTimeout();
+  mock.pushTimeout();
+  const options = { buttons: "YesNo", timeout: 5000 };
+  assert.equal(await message({ ...options, defaultResult: "No" }), "No");
+  assert.equal(await message(options), "Cancel");
+  assert.equal(mock.requests().at(-1).timeout, 5000);
+  mock.pushTimeout();
+  assert.throws(() => new MessageDialog().show(), {
+    code: ErrorCode.InvalidArg,
+  });
+});
+
+test("an aborted signal rejects without showing the dialog", async () => {
+  mock.pushPaths(["/tmp/hello.txt"]);
+  const signal = AbortSignal.abort();
+  await assert.rejects(new AsyncFileDialog().pickFile({ signal }), {
+    name: "AbortError",
+    code: ErrorCode.Cancelled,
+  });
+  await assert.rejects(openFile({ signal }), { name: "AbortError" });
+  assert.deepEqual(mock.requests(), []);
+});
+
+test("the abort listener is removed once the dialog is done", async () => {
+  mock.pushButton(true);
+  const controller = new AbortController();
+  const { signal } = controller;
+  const answer = await new AsyncMessageDialog().show({ signal });
+  assert.equal(answer, MessageDialogResult.Ok);
+  assert.equal(getEventListeners(signal, "abort").length, 0);
+});
+
+test("openFile() and friends take a plain options object", async () => {
+  const options = {
+    title: "Open",
+    filters: [{ name: "Text", extensions: ["txt"] }],
+    directory: "/tmp",
+  };
+  mock.pushPaths(["/tmp/a.txt"]);
+  mock.pushPaths(["/tmp/a.txt", "/tmp/b.txt"]);
+  mock.pushPaths(["/tmp/a.txt", "/tmp/b.txt"]);
+  mock.pushCancel();
+  mock.pushButton(true);
+  assert.equal((await openFile(options)).path(), "/tmp/a.txt");
+  const fileHandles = await openFile({ ...options, multiple: true });
+  assert.deepEqual(
+    fileHandles.map((x) => x.path()),
+    ["/tmp/a.txt", "/tmp/b.txt"],
+  );
+  assert.equal((await openFiles()).length, 2);
+  assert.equal(await saveFile({ fileName: "hello.txt" }), null);
+  assert.equal(await message({ buttons: "OkCancel" }), "Ok");
+  assert.deepEqual(
+    mock.requests().map((x) => x.method),
+    ["pickFile", "pickFiles", "pickFiles", "saveFile", "show"],
+  );
+  assert.deepEqual(mock.requests()[0], { method: "pickFile", ...options });
+  assert.equal(mock.requests()[3].fileName, "hello.txt");
+  assert.equal(mock.requests()[4].buttons, "OkCancel");
+});
+
+test("setParent() records the native window handle", async () => {
+  mock.pushCancel();
+  mock.pushButton(true);
+  new FileDialog().setParent(0x3a00007).pickFile();
+  await new AsyncMessageDialog().setParent(0x3a00007n).show();
+  assert.match(mock.requests()[0].parent, /:0x3a00007$/);
+  assert.equal(mock.requests()[1].parent, mock.requests()[0].parent);
+  assert.throws(() => new FileDialog().setParent(-1), {
+    code: ErrorCode.InvalidArg,
+  });
+});
+
+test("builders can be reused and cloned", async () => {
+  mock.pushCancel();
+  mock.pushCancel();
+  mock.pushCancel();
+  const template = new AsyncFileDialog().setTitle("Pick a project file");
+  const dialog = template.setDirectory("/tmp");
+  assert.equal(dialog, template);
+  const copy = template.clone().setTitle("Pick another project file");
+  assert.notEqual(copy, template);
+  await template.pickFile();
+  await template.pickFile();
+  await copy.pickFile();
+  assert.deepEqual(
+    mock.requests().map((x) => [x.title, x.directory]),
+    [
+      ["Pick a project file", "/tmp"],
+      ["Pick a project file", "/tmp"],
+      ["Pick another project file", "/tmp"],
+    ],
+  );
+});
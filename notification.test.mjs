@@ -0,0 +1,191 @@
+import test from "node:test";
+import assert from "node:assert";
+import { spawn, spawnSync } from "node:child_process";
+import {
+  existsSync,
+  mkdtempSync,
+  readFileSync,
+  rmSync,
+  writeFileSync,
+} from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { createInterface } from "node:readline";
+import { ErrorCode, Notification } from "./index.js";
+
+// A stand-in for a notification server on a private session bus. It records
+// each notification in request.json and answers with the contents of
+// answer.json: an action to invoke, a reason to close it with, or neither to
+// leave it open until it's closed, which is recorded in closed.json. Every
+// answer is preceded by signals for somebody else's notification.
+const mockServer = `
+import json, os, sys
+import dbus, dbus.service
+from dbus.mainloop.glib import DBusGMainLoop
+from gi.repository import GLib
+
+DBusGMainLoop(set_as_default=True)
+bus = dbus.SessionBus()
+name = dbus.service.BusName("org.freedesktop.Notifications", bus)
+state = sys.argv[1]
+INTERFACE = "org.freedesktop.Notifications"
+
+class Notifications(dbus.service.Object):
+    next_id = 1
+
+    @dbus.service.signal(INTERFACE, signature="us")
+    def ActionInvoked(self, id, action):
+        pass
+
+    @dbus.service.signal(INTERFACE, signature="uu")
+    def NotificationClosed(self, id, reason):
+        pass
+
+    @dbus.service.method(INTERFACE, in_signature="susssasa{sv}i", out_signature="u")
+    def Notify(self, app_name, replaces_id, app_icon, summary, body, actions, hints, expire_timeout):
+        id = self.next_id
+        self.next_id += 1
+        with open(os.path.join(state, "request.json"), "w") as f:
+            json.dump({
+                "icon": str(app_icon),
+                "summary": str(summary),
+                "body": str(body),
+                "actions": [str(x) for x in actions],
+                "hints": {str(k): int(v) for k, v in hints.items()},
+                "timeout": int(expire_timeout),
+            }, f)
+        with open(os.path.join(state, "answer.json")) as f:
+            answer = json.load(f)
+        def respond():
+            self.ActionInvoked(dbus.UInt32(id + 100), "other")
+            self.NotificationClosed(dbus.UInt32(id + 100), dbus.UInt32(2))
+            if "action" in answer:
+                self.ActionInvoked(dbus.UInt32(id), answer["action"])
+                self.NotificationClosed(dbus.UInt32(id), dbus.UInt32(2))
+            elif "reason" in answer:
+                self.NotificationClosed(dbus.UInt32(id), dbus.UInt32(answer["reason"]))
+        GLib.idle_add(respond)
+        return dbus.UInt32(id)
+
+    @dbus.service.method(INTERFACE, in_signature="u", out_signature="")
+    def CloseNotification(self, id):
+        with open(os.path.join(state, "closed.json"), "w") as f:
+            json.dump(int(id), f)
+        self.NotificationClosed(id, dbus.UInt32(3))
+
+Notifications(bus, "/org/freedesktop/Notifications")
+print("ready", flush=True)
+GLib.MainLoop().run()
+`;
+
+const skip =
+  process.platform !== "linux" ||
+  spawnSync("dbus-daemon", ["--version"]).status !== 0 ||
+  spawnSync("python3", ["-c", "import dbus, gi"]).status !== 0;
+
+const state = mkdtempSync(join(tmpdir(), "rfd-notification-"));
+let daemon;
+let server;
+
+function answer(x) {
+  writeFileSync(join(state, "answer.json"), JSON.stringify(x));
+}
+
+function request() {
+  return JSON.parse(readFileSync(join(state, "request.json"), "utf8"));
+}
+
+async function firstLine(child) {
+  const lines = createInterface({ input: child.stdout });
+  for await (const line of lines) {
+    return line;
+  }
+}
+
+test.before(async () => {
+  if (skip) {
+    return;
+  }
+  daemon = spawn("dbus-daemon", ["--session", "--nofork", "--print-address"]);
+  const address = await firstLine(daemon);
+  writeFileSync(join(state, "server.py"), mockServer);
+  server = spawn("python3", [join(state, "server.py"), state], {
+    env: { ...process.env, DBUS_SESSION_BUS_ADDRESS: address },
+    stdio: ["ignore", "pipe", "inherit"],
+  });
+  assert.equal(await firstLine(server), "ready");
+  process.env.DBUS_SESSION_BUS_ADDRESS = address;
+});
+
+test.after(() => {
+  server?.kill();
+  daemon?.kill();
+});
+
+test("show() sends Notify with the options", { skip }, async () => {
+  answer({ reason: 1 });
+  const action = await new Notification()
+    .setTitle("Download finished")
+    .setBody("cat café.png")
+    .setIcon("/tmp/cat café.png")
+    .setUrgency("Error")
+    .addAction("open", "Open")
+    .addAction("show", "Show in folder")
+    .setTimeout(5000)
+    .show();
+  assert.equal(action, null);
+  assert.deepEqual(request(), {
+    icon: "file:///tmp/cat%20caf%C3%A9.png",
+    summary: "Download finished",
+    body: "cat café.png",
+    actions: ["open", "Open", "show", "Show in folder"],
+    hints: { urgency: 2 },
+    timeout: 5000,
+  });
+});
+
+test("show() resolves to the invoked action", { skip }, async () => {
+  answer({ action: "open" });
+  const action = await new Notification().addAction("open", "Open").show();
+  assert.equal(action, "open");
+  const { hints, timeout } = request();
+  assert.deepEqual(hints, {});
+  assert.equal(timeout, -1);
+  answer({ reason: 1 });
+  await new Notification().setUrgency("Info").show();
+  assert.deepEqual(request().hints, { urgency: 1 });
+  assert.equal(request().icon, "dialog-information");
+  answer({ reason: 1 });
+  await new Notification().setUrgency("Warning").show();
+  assert.deepEqual(request().hints, { urgency: 1 });
+  assert.equal(request().icon, "dialog-warning");
+});
+
+test("aborting the signal closes the notification", { skip }, async () => {
+  answer({});
+  rmSync(join(state, "request.json"), { force: true });
+  const controller = new AbortController();
+  const action = new Notification().show({ signal: controller.signal });
+  while (!existsSync(join(state, "request.json"))) {
+    await new Promise((resolve) => setTimeout(resolve, 10));
+  }
+  controller.abort();
+  await assert.rejects(action, {
+    name: "AbortError",
+    code: ErrorCode.Cancelled,
+  });
+  const closed = JSON.parse(readFileSync(join(state, "closed.json"), "utf8"));
+  assert.equal(typeof closed, "number");
+});
+
+test("a missing server is ERR_BACKEND_UNAVAILABLE", { skip }, async () => {
+  const address = process.env.DBUS_SESSION_BUS_ADDRESS;
+  process.env.DBUS_SESSION_BUS_ADDRESS = `unix:path=${join(state, "none")}`;
+  try {
+    await assert.rejects(new Notification().show(), {
+      code: ErrorCode.BackendUnavailable,
+    });
+  } finally {
+    process.env.DBUS_SESSION_BUS_ADDRESS = address;
+  }
+});
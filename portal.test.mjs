@@ -0,0 +1,250 @@
+import test from "node:test";
+import assert from "node:assert";
+import { spawn, spawnSync } from "node:child_process";
+import {
+  existsSync,
+  mkdtempSync,
+  readFileSync,
+  rmSync,
+  writeFileSync,
+} from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { createInterface } from "node:readline";
+import { AsyncFileDialog, ErrorCode, FileDialog } from "./index.js";
+
+// A stand-in for xdg-desktop-portal's FileChooser on a private session bus. It
+// records each call in request.json and answers with the contents of
+// answer.json. A negative response leaves the dialog open until it's closed,
+// which is recorded in closed.json.
+const mockPortal = `
+import json, os, sys
+import dbus, dbus.service
+from dbus.mainloop.glib import DBusGMainLoop
+from gi.repository import GLib
+
+DBusGMainLoop(set_as_default=True)
+bus = dbus.SessionBus()
+name = dbus.service.BusName("org.freedesktop.portal.Desktop", bus)
+state = sys.argv[1]
+
+def plain(value):
+    if isinstance(value, dbus.Array) and value.signature == "y":
+        return bytes(value).rstrip(b"\\0").decode()
+    if isinstance(value, dbus.Dictionary):
+        return {str(k): plain(v) for k, v in value.items()}
+    if isinstance(value, (dbus.Array, dbus.Struct)):
+        return [plain(x) for x in value]
+    if isinstance(value, dbus.Boolean):
+        return bool(value)
+    if isinstance(value, (dbus.UInt32, dbus.Int32)):
+        return int(value)
+    return str(value)
+
+class Request(dbus.service.Object):
+    @dbus.service.signal("org.freedesktop.portal.Request", signature="ua{sv}")
+    def Response(self, response, results):
+        pass
+
+    @dbus.service.method("org.freedesktop.portal.Request", in_signature="", out_signature="")
+    def Close(self):
+        with open(os.path.join(state, "closed.json"), "w") as f:
+            json.dump(str(self.__dbus_object_path__), f)
+        self.remove_from_connection()
+
+class FileChooser(dbus.service.Object):
+    def handle(self, method, parent, title, options, sender):
+        with open(os.path.join(state, "request.json"), "w") as f:
+            json.dump({"method": method, "parent": str(parent), "title": str(title), "options": plain(options)}, f)
+        with open(os.path.join(state, "answer.json")) as f:
+            answer = json.load(f)
+        token = str(options["handle_token"])
+        path = "/org/freedesktop/portal/desktop/request/%s/%s" % (sender[1:].replace(".", "_"), token)
+        request = Request(bus, path)
+        results = dbus.Dictionary({"uris": dbus.Array(answer["uris"], signature="s")}, signature="sv")
+        def respond():
+            request.Response(dbus.UInt32(answer["response"]), results)
+            request.remove_from_connection()
+        if answer["response"] >= 0:
+            GLib.idle_add(respond)
+        return dbus.ObjectPath(path)
+
+    @dbus.service.method("org.freedesktop.portal.FileChooser", in_signature="ssa{sv}", out_signature="o", sender_keyword="sender")
+    def OpenFile(self, parent, title, options, sender):
+        return self.handle("OpenFile", parent, title, options, sender)
+
+    @dbus.service.method("org.freedesktop.portal.FileChooser", in_signature="ssa{sv}", out_signature="o", sender_keyword="sender")
+    def SaveFile(self, parent, title, options, sender):
+        return self.handle("SaveFile", parent, title, options, sender)
+
+    @dbus.service.method("org.freedesktop.portal.FileChooser", in_signature="ssa{sv}", out_signature="o", sender_keyword="sender")
+    def SaveFiles(self, parent, title, options, sender):
+        return self.handle("SaveFiles", parent, title, options, sender)
+
+FileChooser(bus, "/org/freedesktop/portal/desktop")
+print("ready", flush=True)
+GLib.MainLoop().run()
+`;
+
+const skip =
+  process.platform !== "linux" ||
+  spawnSync("dbus-daemon", ["--version"]).status !== 0 ||
+  spawnSync("python3", ["-c", "import dbus, gi"]).status !== 0;
+
+const state = mkdtempSync(join(tmpdir(), "rfd-portal-"));
+let daemon;
+let portal;
+
+function answer(response, uris = []) {
+  writeFileSync(join(state, "answer.json"), JSON.stringify({ response, uris }));
+}
+
+function request() {
+  return JSON.parse(readFileSync(join(state, "request.json"), "utf8"));
+}
+
+async function firstLine(child) {
+  const lines = createInterface({ input: child.stdout });
+  for await (const line of lines) {
+    return line;
+  }
+}
+
+test.before(async () => {
+  if (skip) {
+    return;
+  }
+  daemon = spawn("dbus-daemon", ["--session", "--nofork", "--print-address"]);
+  const address = await firstLine(daemon);
+  writeFileSync(join(state, "portal.py"), mockPortal);
+  portal = spawn("python3", [join(state, "portal.py"), state], {
+    env: { ...process.env, DBUS_SESSION_BUS_ADDRESS: address },
+    stdio: ["ignore", "pipe", "inherit"],
+  });
+  assert.equal(await firstLine(portal), "ready");
+  process.env.DBUS_SESSION_BUS_ADDRESS = address;
+  process.env.RFD_BACKEND = "portal";
+});
+
+test.after(() => {
+  portal?.kill();
+  daemon?.kill();
+  delete process.env.RFD_BACKEND;
+});
+
+test("pickFile() calls OpenFile with the options", { skip }, () => {
+  answer(0, ["file:///home/user/hello.txt"]);
+  const path = new FileDialog()
+    .addFilter("Text", ["txt", "md"])
+    .addFilter("All Files", ["*"])
+    .setDirectory("/home/user")
+    .setTitle("Open")
+    .pickFile();
+  assert.equal(path, "/home/user/hello.txt");
+  const { method, parent, title, options } = request();
+  assert.equal(method, "OpenFile");
+  assert.equal(parent, "");
+  assert.equal(title, "Open");
+  assert.equal(options.current_folder, "/home/user");
+  assert.equal(options.modal, false);
+  assert.equal(options.multiple, undefined);
+  assert.deepEqual(options.filters, [
+    ["Text", [[0, "*.txt"], [0, "*.md"]]],
+    ["All Files", [[0, "*"]]],
+  ]);
+});
+
+test("pickFiles() returns document portal paths", { skip }, async () => {
+  answer(0, [
+    "file:///run/user/1000/doc/1a2b3c/My%20Notes.txt",
+    "file:///run/user/1000/doc/4d5e6f/caf%C3%A9.txt",
+  ]);
+  const fileHandles = await new AsyncFileDialog().pickFiles();
+  assert.deepEqual(
+    fileHandles.map((x) => x.path()),
+    [
+      "/run/user/1000/doc/1a2b3c/My Notes.txt",
+      "/run/user/1000/doc/4d5e6f/café.txt",
+    ],
+  );
+  assert.equal(request().options.multiple, true);
+});
+
+test("pickFolders() uses directory mode", { skip }, () => {
+  answer(0, ["file:///home/user/a", "file:///home/user/b"]);
+  const paths = new FileDialog().pickFolders();
+  assert.deepEqual(paths, ["/home/user/a", "/home/user/b"]);
+  const { method, options } = request();
+  assert.equal(method, "OpenFile");
+  assert.equal(options.directory, true);
+  assert.equal(options.multiple, true);
+});
+
+test("saveFile() calls SaveFile with the current name", { skip }, async () => {
+  answer(0, ["file:///home/user/report.pdf"]);
+  const fileHandle = await new AsyncFileDialog()
+    .setDirectory("/home/user")
+    .setFileName("report.pdf")
+    .setParent(0x3a00007)
+    .saveFile();
+  assert.equal(fileHandle.path(), "/home/user/report.pdf");
+  const { method, parent, options } = request();
+  assert.equal(method, "SaveFile");
+  assert.equal(parent, "x11:3a00007");
+  assert.equal(options.modal, true);
+  assert.equal(options.current_name, "report.pdf");
+  assert.equal(options.current_folder, "/home/user");
+});
+
+test("saveFiles() calls SaveFiles with the file names", { skip }, () => {
+  answer(0, ["file:///home/user/cat.png", "file:///home/user/dog.png"]);
+  const paths = new FileDialog().saveFiles(["cat.png", "dog.png"]);
+  assert.deepEqual(paths, ["/home/user/cat.png", "/home/user/dog.png"]);
+  const { method, options } = request();
+  assert.equal(method, "SaveFiles");
+  assert.deepEqual(options.files, ["cat.png", "dog.png"]);
+});
+
+test("response 1 means the dialog was cancelled", { skip }, async () => {
+  answer(1);
+  assert.equal(new FileDialog().pickFile(), null);
+  assert.equal(await new AsyncFileDialog().saveFile(), null);
+});
+
+test("other responses are ERR_BACKEND_UNAVAILABLE", { skip }, async () => {
+  answer(2);
+  await assert.rejects(new AsyncFileDialog().pickFile(), {
+    code: ErrorCode.BackendUnavailable,
+  });
+});
+
+test("aborting the signal closes the request", { skip }, async () => {
+  answer(-1);
+  rmSync(join(state, "request.json"), { force: true });
+  const controller = new AbortController();
+  const fileHandle = new AsyncFileDialog().pickFile({
+    signal: controller.signal,
+  });
+  while (!existsSync(join(state, "request.json"))) {
+    await new Promise((resolve) => setTimeout(resolve, 10));
+  }
+  controller.abort();
+  await assert.rejects(fileHandle, {
+    name: "AbortError",
+    code: ErrorCode.Cancelled,
+  });
+  const closed = JSON.parse(readFileSync(join(state, "closed.json"), "utf8"));
+  assert.match(closed, /^\/org\/freedesktop\/portal\/desktop\/request\//);
+});
+
+test("a missing portal is ERR_BACKEND_UNAVAILABLE", { skip }, () => {
+  const address = process.env.DBUS_SESSION_BUS_ADDRESS;
+  process.env.DBUS_SESSION_BUS_ADDRESS = `unix:path=${join(state, "none")}`;
+  try {
+    assert.throws(() => new FileDialog().pickFile(), {
+      code: ErrorCode.BackendUnavailable,
+    });
+  } finally {
+    process.env.DBUS_SESSION_BUS_ADDRESS = address;
+  }
+});
@@ -0,0 +1,185 @@
+use super::error::*;
+use napi::bindgen_prelude::*;
+use napi::tokio::sync::Notify;
+use napi::{JsFunction, JsObject, JsUnknown, Ref};
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::Poll;
+
+struct AbortState {
+    aborted: AtomicBool,
+    notify: Notify,
+}
+
+/// The Rust side of an `AbortSignal`. It can be checked from any thread and
+/// awaited on the async runtime. The default token is never aborted, which is
+/// what the synchronous dialogs use.
+#[derive(Clone, Default)]
+pub(crate) struct AbortToken(Option<Arc<AbortState>>);
+impl AbortToken {
+    fn new() -> Self {
+        return Self(Some(Arc::new(AbortState {
+            aborted: AtomicBool::new(false),
+            notify: Notify::new(),
+        })));
+    }
+
+    fn abort(&self) {
+        if let Some(state) = &self.0 {
+            state.aborted.store(true, Ordering::SeqCst);
+            state.notify.notify_waiters();
+        }
+    }
+
+    pub(crate) fn is_aborted(&self) -> bool {
+        return match &self.0 {
+            Some(state) => state.aborted.load(Ordering::SeqCst),
+            None => false,
+        };
+    }
+
+    /// Resolves once the signal is aborted, or never for the default token.
+    pub(crate) async fn aborted(&self) {
+        let state = match &self.0 {
+            Some(state) => state,
+            None => return std::future::pending().await,
+        };
+        loop {
+            let notified = state.notify.notified();
+            if state.aborted.load(Ordering::SeqCst) {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Runs `future` until it finishes or the signal is aborted, whichever
+    /// comes first. The future is dropped on abort.
+    pub(crate) async fn race<F: Future>(&self, future: F) -> RfdResult<F::Output> {
+        let mut future = pin!(future);
+        let mut aborted = pin!(self.aborted());
+        return poll_fn(|cx| {
+            if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                return Poll::Ready(Ok(output));
+            }
+            if aborted.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Err(RfdError::aborted()));
+            }
+            return Poll::Pending;
+        })
+        .await;
+    }
+}
+
+/// An `abort` event listener on a JavaScript `AbortSignal`. It's removed again
+/// when the dialog finishes so that signals can be reused for many dialogs.
+pub struct AbortListener {
+    token: AbortToken,
+    refs: Option<(Ref<()>, Ref<()>)>,
+}
+impl AbortListener {
+    pub(crate) fn token(&self) -> AbortToken {
+        return self.token.clone();
+    }
+
+    fn remove(mut self, env: Env) -> Result<()> {
+        if let Some((mut signal, mut listener)) = self.refs.take() {
+            let signal_object: JsObject = env.get_reference_value(&signal)?;
+            let listener_function: JsFunction = env.get_reference_value(&listener)?;
+            let remove: JsFunction = signal_object.get_named_property("removeEventListener")?;
+            let args: [JsUnknown; 2] = [
+                env.create_string("abort")?.into_unknown(),
+                listener_function.into_unknown(),
+            ];
+            remove.call(Some(&signal_object), &args)?;
+            signal.unref(env)?;
+            listener.unref(env)?;
+        }
+        return Ok(());
+    }
+}
+impl TypeName for AbortListener {
+    fn type_name() -> &'static str {
+        return "AbortSignal";
+    }
+
+    fn value_type() -> ValueType {
+        return ValueType::Object;
+    }
+}
+impl ValidateNapiValue for AbortListener {}
+impl FromNapiValue for AbortListener {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+        let env = Env::from_raw(env);
+        let signal = JsObject::from_napi_value(env.raw(), napi_val)?;
+        let token = AbortToken::new();
+        let aborted: bool = signal.get_named_property("aborted")?;
+        if aborted {
+            token.abort();
+            return Ok(Self { token, refs: None });
+        }
+        let listener_token = token.clone();
+        let listener = env.create_function_from_closure("onabort", move |_| {
+            listener_token.abort();
+            return Ok(());
+        })?;
+        let add: JsFunction = signal.get_named_property("addEventListener")?;
+        let args: [JsUnknown; 2] = [
+            env.create_string("abort")?.into_unknown(),
+            listener.into_unknown(),
+        ];
+        add.call(Some(&signal), &args)?;
+        let refs = (
+            env.create_reference(signal)?,
+            env.create_reference(&args[1])?,
+        );
+        return Ok(Self {
+            token,
+            refs: Some(refs),
+        });
+    }
+}
+
+/// The last argument of the terminal methods of `AsyncFileDialog` and
+/// `AsyncMessageDialog`. Aborting the `signal` rejects the promise with an
+/// `AbortError` (with `code` set to `ERR_CANCELLED`). The `zenity`, `kdialog`
+/// and `portal` backends also close the dialog, while the native dialogs of
+/// `rfd` stay open until the user closes them since they can't be closed from
+/// the outside.
+///
+/// Example:
+///
+/// ```js
+/// const fileHandle = await new AsyncFileDialog().pickFile({
+///   signal: AbortSignal.timeout(60_000),
+/// });
+/// ```
+#[napi(object, object_to_js = false)]
+pub struct AbortOptions {
+    #[napi(ts_type = "AbortSignal")]
+    pub signal: Option<AbortListener>,
+}
+
+/// The token of the signal in `options`, if there is one.
+pub(crate) fn abort_token(options: &Option<AbortOptions>) -> AbortToken {
+    return options
+        .as_ref()
+        .and_then(|x| x.signal.as_ref())
+        .map(|x| x.token())
+        .unwrap_or_default();
+}
+
+/// What the async terminal methods that take `AbortOptions` return. This is an
+/// `AsyncResult` that also removes the `abort` listener once the promise
+/// settles, which has to happen on the main thread.
+pub struct AbortableResult<T>(pub(crate) RfdResult<T>, pub(crate) Option<AbortOptions>);
+impl<T: ToNapiValue> ToNapiValue for AbortableResult<T> {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+        if let Some(listener) = val.1.and_then(|x| x.signal) {
+            listener.remove(Env::from_raw(env))?;
+        }
+        return AsyncResult::to_napi_value(env, AsyncResult(val.0));
+    }
+}
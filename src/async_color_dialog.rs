@@ -0,0 +1,132 @@
+use super::abort::*;
+use super::backend;
+use super::color_dialog::*;
+use super::error::*;
+use super::parent_window::*;
+use napi::bindgen_prelude::*;
+
+/// The builder for an asynchronous color picker. See `ColorDialog` for which
+/// backends can show it.
+///
+/// Example:
+///
+/// ```js
+/// const color = await new AsyncColorDialog()
+///     .setTitle('Highlight color')
+///     .setColor('#ffff00')
+///     .show();
+/// console.log(color?.hex);
+/// //=> '#ff8800'
+/// ```
+#[napi]
+#[repr(transparent)]
+pub struct AsyncColorDialog(pub(super) ColorOptions);
+impl Default for AsyncColorDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[napi]
+impl AsyncColorDialog {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        return Self(ColorOptions::default());
+    }
+
+    /// Returns a copy of this color dialog with the same settings.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const pick = new AsyncColorDialog().setAlpha(true);
+    /// const color = await pick.clone().setColor('#00000080').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
+    }
+
+    /// Sets the title of the color dialog. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const color = await new AsyncColorDialog().setTitle('Text color').show();
+    /// ```
+    #[napi]
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Sets the color that is selected when the dialog opens. See
+    /// `ColorDialog.setColor()`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const color = await new AsyncColorDialog().setColor('#ff8800').show();
+    /// ```
+    #[napi]
+    pub fn set_color(&mut self, this: This, hex: String) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.color = Some(Rgba::from_hex(&hex)?);
+        return Ok(this);
+    }
+
+    /// Lets the user pick the opacity too. See `ColorDialog.setAlpha()`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const { a } = await new AsyncColorDialog().setAlpha(true).show();
+    /// ```
+    #[napi]
+    pub fn set_alpha(&mut self, this: This, alpha: bool) -> This {
+        let x = &mut self.0;
+        x.alpha = alpha;
+        return this;
+    }
+
+    /// Makes the color dialog modal for a parent window. See
+    /// `MessageDialog.setParent()` for the accepted handles.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const color = await new AsyncColorDialog().setParent(handle).show();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
+    }
+
+    /// Shows the color dialog and returns a promise that resolves to the picked
+    /// color, or `null` if the dialog was cancelled.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const color = await new AsyncColorDialog().show();
+    /// if (color) {
+    ///   console.log(color.r, color.g, color.b);
+    /// }
+    /// ```
+    #[napi(ts_return_type = "Promise<Color | null>")]
+    pub async unsafe fn show(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<Color>> {
+        let x = self.0.clone();
+        let y = backend::async_color_dialog(x.clone(), abort_token(&options)).await;
+        return AbortableResult(y.map(|y| y.map(|y| x.result(y))), options);
+    }
+}
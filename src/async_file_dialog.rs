@@ -1,11 +1,16 @@
+use super::abort::*;
+use super::backend;
+use super::error::*;
+use super::file_dialog::*;
 use super::file_handle::*;
+use super::parent_window::*;
 use napi::bindgen_prelude::*;
 use rfd;
 
 /// An asynchronous file dialog builder. Use this to open a file dialog as a
 /// Promise instance. The `.pickFile()` and other methods will return a
-/// `Promise` and invalidate the builder object. You can only use the builder to
-/// perform a single `.pickFile()` or similar operation.
+/// `Promise`. The builder keeps its configuration afterwards, so it can be used
+/// for any number of `.pickFile()` or similar operations.
 ///
 /// Unlike the synchronous version, this doesn't return the path string directly
 /// but instead wraps it in a `FileHandle` instance (not the Node.js `node:fs`
@@ -23,12 +28,36 @@ use rfd;
 /// ```
 #[napi]
 #[repr(transparent)]
-pub struct AsyncFileDialog(pub(super) Option<rfd::AsyncFileDialog>);
+pub struct AsyncFileDialog(pub(super) FileOptions);
+impl Default for AsyncFileDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
 #[napi]
 impl AsyncFileDialog {
     #[napi(constructor)]
     pub fn new() -> Self {
-        return Self(Some(rfd::AsyncFileDialog::new()));
+        return Self(FileOptions::default());
+    }
+
+    /// Returns an independent copy of this builder. Since a builder can be
+    /// shown any number of times, define it once and clone it whenever you
+    /// need a slightly different dialog.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const pickProjectFile = new AsyncFileDialog()
+    ///     .addFilter('Project', ['proj'])
+    ///     .setTitle('Pick a project file');
+    /// const fileHandle = await pickProjectFile.pickFile();
+    /// const other = await pickProjectFile.clone().setDirectory('/tmp').pickFile();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
     }
 
     /// Add a file filter. The first argument is the name of the filter, the
@@ -48,15 +77,13 @@ impl AsyncFileDialog {
     /// //=> '/home/username/Documents/script.js'
     /// ```
     #[napi]
-    pub fn add_filter(&mut self, name: String, ext: Vec<String>) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        // https://users.rust-lang.org/t/vec-string-to-str/12619/2
-        let ext: Vec<&str> = ext.iter().map(<_>::as_ref).collect();
-        let x = x.add_filter(&name, &ext);
-        return Ok(Self(Some(x)));
+    pub fn add_filter(&mut self, this: This, name: String, ext: Vec<String>) -> This {
+        let x = &mut self.0;
+        x.filters.push(FileFilter {
+            name,
+            extensions: ext,
+        });
+        return this;
     }
 
     /// Set the directory that the file dialog will open in. By default this is
@@ -76,13 +103,10 @@ impl AsyncFileDialog {
     /// //=> '/home/username/Pictures/image.jpeg'
     /// ```
     #[napi]
-    pub fn set_directory(&mut self, dir: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_directory(&dir);
-        return Ok(Self(Some(x)));
+    pub fn set_directory(&mut self, this: This, dir: String) -> This {
+        let x = &mut self.0;
+        x.directory = Some(dir);
+        return this;
     }
 
     /// Set the default file name that will be pre-filled in the file dialog
@@ -102,13 +126,10 @@ impl AsyncFileDialog {
     /// await writeFile(fileHandle.path(), 'Hello, world!');
     /// ```
     #[napi]
-    pub fn set_file_name(&mut self, name: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_file_name(&name);
-        return Ok(Self(Some(x)));
+    pub fn set_file_name(&mut self, this: This, name: String) -> This {
+        let x = &mut self.0;
+        x.file_name = Some(name);
+        return this;
     }
 
     /// Set the title of the file dialog. That's the thing that appears on the
@@ -122,22 +143,38 @@ impl AsyncFileDialog {
     /// new AsyncFileDialog().setTitle('Save your document');
     /// ```
     #[napi]
-    pub fn set_title(&mut self, title: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_title(&title);
-        return Ok(Self(Some(x)));
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Attaches the dialog to a parent window so that it's modal for that
+    /// window. See `FileDialog.setParent()` for the accepted handles.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog()
+    ///     .setParent(0x3a00007)
+    ///     .pickFile();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
     }
 
-    /// Actually pick the file and consume the builder. This will return a
-    /// `Promise` that resolves to a `FileHandle` instance. The `FileHandle`
-    /// instance can be used to get the path of the file that was picked.
+    /// Actually pick the file. This will return a `Promise` that resolves to a
+    /// `FileHandle` instance. The `FileHandle` instance can be used to get the
+    /// path of the file that was picked.
     ///
-    /// Note that this **will invalidate the builder**. The recommended pattern
-    /// is to construct and consume the builder in one continuous chain like
-    /// this example:
+    /// Example:
     ///
     /// ```js
     /// const fileHandle = await new AsyncFileDialog()
@@ -147,18 +184,26 @@ impl AsyncFileDialog {
     /// console.log(fileHandle.path());
     /// //=> '/home/username/Pictures/image.png'
     /// ```
-    #[napi]
-    pub async unsafe fn pick_file(&mut self) -> Result<Option<FileHandle>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
+    #[napi(ts_return_type = "Promise<FileHandle | null>")]
+    pub async unsafe fn pick_file(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<FileHandle>> {
+        let x = self.0.clone();
+        let path_buf =
+            backend::async_file_dialog(FileMethod::PickFile, x, abort_token(&options)).await;
+        if path_buf.is_err() {
+            return AbortableResult(Err(path_buf.unwrap_err()), options);
         }
-        let x = self.0.take().unwrap();
-        let handle = x.pick_file().await;
-        if handle.is_none() {
-            return Ok(None);
+        let path_buf = path_buf.unwrap();
+        if path_buf.is_none() {
+            return AbortableResult(Ok(None), options);
         }
-        let handle = handle.unwrap();
-        return Ok(Some(FileHandle(handle)));
+        let path_buf = path_buf.unwrap();
+        return AbortableResult(
+            Ok(Some(FileHandle(rfd::FileHandle::from(path_buf)))),
+            options,
+        );
     }
 
     /// Same as `.pickFile()` but allows the user to select multiple files.
@@ -173,22 +218,27 @@ impl AsyncFileDialog {
     ///  .pickFiles();
     /// console.log(fileHandles.map(handle => handle.path()));
     /// //=> ['/home/username/Pictures/image.png', ...]
-    #[napi]
-    pub async unsafe fn pick_files(&mut self) -> Result<Option<Vec<FileHandle>>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
+    #[napi(ts_return_type = "Promise<Array<FileHandle> | null>")]
+    pub async unsafe fn pick_files(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<Vec<FileHandle>>> {
+        let x = self.0.clone();
+        let path_bufs =
+            backend::async_file_dialogs(FileMethod::PickFiles, x, abort_token(&options)).await;
+        if path_bufs.is_err() {
+            return AbortableResult(Err(path_bufs.unwrap_err()), options);
         }
-        let x = self.0.take().unwrap();
-        let handles = x.pick_files().await;
-        if handles.is_none() {
-            return Ok(None);
+        let path_bufs = path_bufs.unwrap();
+        if path_bufs.is_none() {
+            return AbortableResult(Ok(None), options);
         }
-        let handles = handles.unwrap();
-        let handles = handles
+        let path_bufs = path_bufs.unwrap();
+        let handles = path_bufs
             .into_iter()
-            .map(|handle| FileHandle(handle))
+            .map(|path_buf| FileHandle(rfd::FileHandle::from(path_buf)))
             .collect();
-        return Ok(Some(handles));
+        return AbortableResult(Ok(Some(handles)), options);
     }
 
     /// Same as `.pickFile()` but allows the user to select a folder. This will
@@ -204,18 +254,26 @@ impl AsyncFileDialog {
     /// console.log(fileHandle.path());
     /// //=> '/home/username/Pictures'
     /// ```
-    #[napi]
-    pub async unsafe fn pick_folder(&mut self) -> Result<Option<FileHandle>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
+    #[napi(ts_return_type = "Promise<FileHandle | null>")]
+    pub async unsafe fn pick_folder(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<FileHandle>> {
+        let x = self.0.clone();
+        let path_buf =
+            backend::async_file_dialog(FileMethod::PickFolder, x, abort_token(&options)).await;
+        if path_buf.is_err() {
+            return AbortableResult(Err(path_buf.unwrap_err()), options);
         }
-        let x = self.0.take().unwrap();
-        let handle = x.pick_folder().await;
-        if handle.is_none() {
-            return Ok(None);
+        let path_buf = path_buf.unwrap();
+        if path_buf.is_none() {
+            return AbortableResult(Ok(None), options);
         }
-        let handle = handle.unwrap();
-        return Ok(Some(FileHandle(handle)));
+        let path_buf = path_buf.unwrap();
+        return AbortableResult(
+            Ok(Some(FileHandle(rfd::FileHandle::from(path_buf)))),
+            options,
+        );
     }
 
     /// Same as `.pickFolder()` but allows the user to select multiple folders.
@@ -232,22 +290,27 @@ impl AsyncFileDialog {
     /// console.log(fileHandles.map(handle => handle.path()));
     /// //=> ['/home/username/Pictures', ...]
     /// ```
-    #[napi]
-    pub async unsafe fn pick_folders(&mut self) -> Result<Option<Vec<FileHandle>>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
+    #[napi(ts_return_type = "Promise<Array<FileHandle> | null>")]
+    pub async unsafe fn pick_folders(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<Vec<FileHandle>>> {
+        let x = self.0.clone();
+        let path_bufs =
+            backend::async_file_dialogs(FileMethod::PickFolders, x, abort_token(&options)).await;
+        if path_bufs.is_err() {
+            return AbortableResult(Err(path_bufs.unwrap_err()), options);
         }
-        let x = self.0.take().unwrap();
-        let handles = x.pick_folders().await;
-        if handles.is_none() {
-            return Ok(None);
+        let path_bufs = path_bufs.unwrap();
+        if path_bufs.is_none() {
+            return AbortableResult(Ok(None), options);
         }
-        let handles = handles.unwrap();
-        let handles = handles
+        let path_bufs = path_bufs.unwrap();
+        let handles = path_bufs
             .into_iter()
-            .map(|handle| FileHandle(handle))
+            .map(|path_buf| FileHandle(rfd::FileHandle::from(path_buf)))
             .collect();
-        return Ok(Some(handles));
+        return AbortableResult(Ok(Some(handles)), options);
     }
 
     /// Same as `.pickFile()` but allows the user to select a file to save to.
@@ -266,17 +329,64 @@ impl AsyncFileDialog {
     /// //=> '/home/username/Downloads/My_Document.txt'
     /// await writeFile(fileHandle.path(), 'Hello, world!');
     /// ```
-    #[napi]
-    pub async unsafe fn save_file(&mut self) -> Result<Option<FileHandle>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
+    #[napi(ts_return_type = "Promise<FileHandle | null>")]
+    pub async unsafe fn save_file(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<FileHandle>> {
+        let x = self.0.clone();
+        let path_buf =
+            backend::async_file_dialog(FileMethod::SaveFile, x, abort_token(&options)).await;
+        if path_buf.is_err() {
+            return AbortableResult(Err(path_buf.unwrap_err()), options);
+        }
+        let path_buf = path_buf.unwrap();
+        if path_buf.is_none() {
+            return AbortableResult(Ok(None), options);
+        }
+        let path_buf = path_buf.unwrap();
+        return AbortableResult(
+            Ok(Some(FileHandle(rfd::FileHandle::from(path_buf)))),
+            options,
+        );
+    }
+
+    /// Lets the user pick where to save several files at once. The XDG desktop
+    /// portal shows a dedicated dialog for this; everywhere else the user picks
+    /// a folder. Returns a `FileHandle` for each of `fileNames` in that folder,
+    /// in the same order, or `null` if the user canceled the dialog.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandles = await new AsyncFileDialog()
+    ///     .setDirectory('~/Pictures')
+    ///     .saveFiles(['cat.png', 'dog.png']);
+    /// console.log(fileHandles.map(handle => handle.path()));
+    /// //=> ['/home/username/Pictures/cat.png', '/home/username/Pictures/dog.png']
+    /// ```
+    #[napi(ts_return_type = "Promise<Array<FileHandle> | null>")]
+    pub async unsafe fn save_files(
+        &self,
+        file_names: Vec<String>,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<Vec<FileHandle>>> {
+        let mut x = self.0.clone();
+        x.file_names = file_names;
+        let path_bufs =
+            backend::async_file_dialogs(FileMethod::SaveFiles, x, abort_token(&options)).await;
+        if path_bufs.is_err() {
+            return AbortableResult(Err(path_bufs.unwrap_err()), options);
         }
-        let x = self.0.take().unwrap();
-        let handle = x.save_file().await;
-        if handle.is_none() {
-            return Ok(None);
+        let path_bufs = path_bufs.unwrap();
+        if path_bufs.is_none() {
+            return AbortableResult(Ok(None), options);
         }
-        let handle = handle.unwrap();
-        return Ok(Some(FileHandle(handle)));
+        let path_bufs = path_bufs.unwrap();
+        let handles = path_bufs
+            .into_iter()
+            .map(|path_buf| FileHandle(rfd::FileHandle::from(path_buf)))
+            .collect();
+        return AbortableResult(Ok(Some(handles)), options);
     }
 }
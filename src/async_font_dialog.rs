@@ -0,0 +1,167 @@
+use super::abort::*;
+use super::backend;
+use super::error::*;
+use super::font_dialog::*;
+use super::parent_window::*;
+use napi::bindgen_prelude::*;
+
+/// The builder for an asynchronous font chooser. See `FontDialog` for which
+/// backends can show it.
+///
+/// Example:
+///
+/// ```js
+/// const font = await new AsyncFontDialog()
+///     .setTitle('Editor font')
+///     .setFamily('Fira Code')
+///     .setSize(12)
+///     .show();
+/// console.log(font?.family);
+/// //=> 'JetBrains Mono'
+/// ```
+#[napi]
+#[repr(transparent)]
+pub struct AsyncFontDialog(pub(super) FontOptions);
+impl Default for AsyncFontDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[napi]
+impl AsyncFontDialog {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        return Self(FontOptions::default());
+    }
+
+    /// Returns a copy of this font dialog with the same settings.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const pick = new AsyncFontDialog().setSize(12);
+    /// const font = await pick.clone().setFamily('Fira Code').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
+    }
+
+    /// Sets the title of the font dialog. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = await new AsyncFontDialog().setTitle('Editor font').show();
+    /// ```
+    #[napi]
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Sets the font family that is selected when the dialog opens. Returns
+    /// `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = await new AsyncFontDialog().setFamily('Fira Code').show();
+    /// ```
+    #[napi]
+    pub fn set_family(&mut self, this: This, family: String) -> This {
+        let x = &mut self.0;
+        x.family = Some(family);
+        return this;
+    }
+
+    /// Sets the style that is selected when the dialog opens. Returns `this`
+    /// for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = await new AsyncFontDialog().setStyle('italic').show();
+    /// ```
+    #[napi]
+    pub fn set_style(&mut self, this: This, style: FontStyle) -> This {
+        let x = &mut self.0;
+        x.style = Some(style);
+        return this;
+    }
+
+    /// Sets the weight that is selected when the dialog opens. See
+    /// `FontDialog.setWeight()`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = await new AsyncFontDialog().setWeight(700).show();
+    /// ```
+    #[napi]
+    pub fn set_weight(&mut self, this: This, weight: u32) -> Result<This, ErrorCode> {
+        check_weight(weight)?;
+        let x = &mut self.0;
+        x.weight = Some(weight);
+        return Ok(this);
+    }
+
+    /// Sets the size in points that is selected when the dialog opens. See
+    /// `FontDialog.setSize()`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = await new AsyncFontDialog().setSize(10.5).show();
+    /// ```
+    #[napi]
+    pub fn set_size(&mut self, this: This, size: f64) -> Result<This, ErrorCode> {
+        check_size(size)?;
+        let x = &mut self.0;
+        x.size = Some(size);
+        return Ok(this);
+    }
+
+    /// Makes the font dialog modal for a parent window. See
+    /// `MessageDialog.setParent()` for the accepted handles.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = await new AsyncFontDialog().setParent(handle).show();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
+    }
+
+    /// Shows the font dialog and returns a promise that resolves to the picked
+    /// font, or `null` if the dialog was cancelled. Like the other builders, it
+    /// can be shown again afterwards.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = await new AsyncFontDialog().show();
+    /// if (font) {
+    ///   console.log(font.family, font.size);
+    /// }
+    /// ```
+    #[napi(ts_return_type = "Promise<Font | null>")]
+    pub async unsafe fn show(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<Font>> {
+        let x = self.0.clone();
+        let y = backend::async_font_dialog(x, abort_token(&options)).await;
+        return AbortableResult(y, options);
+    }
+}
@@ -0,0 +1,170 @@
+use super::abort::*;
+use super::backend;
+use super::error::*;
+use super::input_dialog::*;
+use super::parent_window::*;
+use napi::bindgen_prelude::*;
+
+/// The builder for an asynchronous text input dialog, the equivalent of the
+/// browser's `prompt()`. See `InputDialog` for which backends can show it.
+///
+/// Example:
+///
+/// ```js
+/// const name = await new AsyncInputDialog()
+///     .setTitle('New folder')
+///     .setDescription('Name of the new folder:')
+///     .setDefaultValue('Untitled')
+///     .show();
+/// console.log(name);
+/// //=> 'Photos'
+/// ```
+#[napi]
+#[repr(transparent)]
+pub struct AsyncInputDialog(pub(super) InputOptions);
+impl Default for AsyncInputDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[napi]
+impl AsyncInputDialog {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        return Self(InputOptions::default());
+    }
+
+    /// Returns a copy of this input dialog with the same settings.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const ask = new AsyncInputDialog().setTitle('Rename');
+    /// const name = await ask.clone().setDefaultValue('notes.txt').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
+    }
+
+    /// Sets the title of the input dialog. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = await new AsyncInputDialog().setTitle('Rename').show();
+    /// ```
+    #[napi]
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Sets the text shown above the input. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = await new AsyncInputDialog()
+    ///     .setDescription('What should the file be called?')
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_description(&mut self, this: This, description: String) -> This {
+        let x = &mut self.0;
+        x.description = Some(description);
+        return this;
+    }
+
+    /// Pre-fills the input. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = await new AsyncInputDialog()
+    ///     .setDefaultValue('notes.txt')
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_default_value(&mut self, this: This, value: String) -> This {
+        let x = &mut self.0;
+        x.default_value = Some(value);
+        return this;
+    }
+
+    /// Masks what the user types. See `InputDialog.setPassword()`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const password = await new AsyncInputDialog()
+    ///     .setDescription('Enter the passphrase for id_ed25519:')
+    ///     .setPassword(true)
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_password(&mut self, this: This, password: bool) -> This {
+        let x = &mut self.0;
+        x.password = password;
+        return this;
+    }
+
+    /// Shows a text area instead of a single line input. See
+    /// `InputDialog.setMultiline()`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const message = await new AsyncInputDialog()
+    ///     .setTitle('Commit message')
+    ///     .setMultiline(true)
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_multiline(&mut self, this: This, multiline: bool) -> This {
+        let x = &mut self.0;
+        x.multiline = multiline;
+        return this;
+    }
+
+    /// Makes the input dialog modal for a parent window. See
+    /// `MessageDialog.setParent()` for the accepted handles.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = await new AsyncInputDialog().setParent(handle).show();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
+    }
+
+    /// Shows the input dialog and returns a promise that resolves to the text
+    /// that was entered, or `null` if the dialog was cancelled.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = await new AsyncInputDialog()
+    ///     .setDescription('Your name:')
+    ///     .show({ signal: AbortSignal.timeout(60_000) });
+    /// ```
+    #[napi(ts_return_type = "Promise<string | null>")]
+    pub async unsafe fn show(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<String>> {
+        let x = self.0.clone();
+        let y = backend::async_input_dialog(x, abort_token(&options)).await;
+        return AbortableResult(y, options);
+    }
+}
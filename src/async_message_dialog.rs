@@ -1,7 +1,12 @@
+use super::abort::*;
+use super::backend;
+use super::error::*;
 use super::message_buttons::*;
+use super::message_dialog::*;
+use super::message_dialog_result::*;
 use super::message_level::*;
+use super::parent_window::*;
 use napi::bindgen_prelude::*;
-use rfd;
 
 /// The builder for an asynchronous message dialog popup window. Use this to
 /// show alert boxes and other messages to the user.
@@ -16,16 +21,36 @@ use rfd;
 ///     .setButtons('Ok')
 ///     .show();
 /// console.log(answer);
-/// //=> true
+/// //=> 'Ok'
 /// ```
 #[napi]
 #[repr(transparent)]
-pub struct AsyncMessageDialog(pub(super) Option<rfd::AsyncMessageDialog>);
+pub struct AsyncMessageDialog(pub(super) MessageOptions);
+impl Default for AsyncMessageDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
 #[napi]
 impl AsyncMessageDialog {
     #[napi(constructor)]
     pub fn new() -> Self {
-        return Self(Some(rfd::AsyncMessageDialog::new()));
+        return Self(MessageOptions::default());
+    }
+
+    /// Returns a copy of this message dialog with the same level, title,
+    /// description and buttons.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const confirm = new AsyncMessageDialog().setButtons('YesNo');
+    /// const answer = await confirm.clone().setTitle('Delete file?').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
     }
 
     /// Sets the level of the message dialog. This determines the icon that is
@@ -40,13 +65,10 @@ impl AsyncMessageDialog {
     ///     .show();
     /// ```
     #[napi]
-    pub fn set_level(&mut self, level: MessageLevel) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_level(level.to_rfd_t());
-        return Ok(Self(Some(x)));
+    pub fn set_level(&mut self, this: This, level: MessageLevel) -> This {
+        let x = &mut self.0;
+        x.level = Some(level);
+        return this;
     }
 
     /// Sets the title of the message dialog. Defaults to an empty string.
@@ -60,13 +82,31 @@ impl AsyncMessageDialog {
     ///     .show();
     /// ```
     #[napi]
-    pub fn set_title(&mut self, title: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_title(&title);
-        return Ok(Self(Some(x)));
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Attaches the message box to a parent window. See
+    /// `MessageDialog.setParent()` for the accepted handles.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const answer = await new AsyncMessageDialog()
+    ///     .setParent(handle)
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
     }
 
     /// Sets the description of the message dialog. Defaults to an empty string.
@@ -80,57 +120,108 @@ impl AsyncMessageDialog {
     ///     .show();
     /// ```
     #[napi]
-    pub fn set_description(&mut self, description: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_description(&description);
-        return Ok(Self(Some(x)));
+    pub fn set_description(&mut self, this: This, description: String) -> This {
+        let x = &mut self.0;
+        x.description = Some(description);
+        return this;
     }
 
-    /// Sets the buttons of the message dialog. Defaults to `Ok`.
+    /// Sets the buttons of the message dialog. Defaults to `Ok`. The
+    /// `...Custom` buttons take their labels from the second argument. See
+    /// `MessageDialog.setButtons()` for details.
     ///
     /// Example:
     ///
     /// ```js
     /// const answer = await new AsyncMessageDialog()
-    ///     .setButtons('Ok')
+    ///     .setTitle('Save changes?')
+    ///     .setButtons('YesNoCancelCustom', ['Save', "Don't Save", 'Cancel'])
     ///     .show();
+    /// if (answer === 'Save') {
+    ///   await save();
+    /// }
     /// ```
     #[napi]
-    pub fn set_buttons(&mut self, buttons: MessageButtons) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_buttons(buttons.to_rfd_t());
-        return Ok(Self(Some(x)));
+    pub fn set_buttons(
+        &mut self,
+        this: This,
+        buttons: MessageButtons,
+        labels: Option<Vec<String>>,
+    ) -> Result<This, ErrorCode> {
+        let labels = labels.unwrap_or_default();
+        buttons.check_labels(&labels)?;
+        let x = &mut self.0;
+        x.buttons = Some(buttons);
+        x.labels = labels;
+        return Ok(this);
     }
 
-    /// Shows the message dialog and returns a promise that resolves to `true`
-    /// if the user clicked the primary button, or `false` if the user clicked
-    /// the secondary button or closed the dialog.
+    /// Closes the dialog after `ms` milliseconds without an answer and
+    /// resolves to `defaultResult` instead. The native dialogs can't be
+    /// closed, so they resolve on time but stay open until they're answered.
+    /// See `MessageDialog.setTimeout()`.
+    ///
+    /// Example:
     ///
-    /// Note that this will **consume the builder** so that it cannot be used
-    /// again!
+    /// ```js
+    /// const answer = await new AsyncMessageDialog()
+    ///     .setDescription('Install updates now?')
+    ///     .setButtons('YesNo')
+    ///     .setTimeout(60_000, 'Yes')
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_timeout(
+        &mut self,
+        this: This,
+        ms: u32,
+        #[napi(ts_arg_type = "MessageDialogResult | string")] default_result: String,
+    ) -> This {
+        let x = &mut self.0;
+        x.timeout = Some((ms, default_result));
+        return this;
+    }
+
+    /// Shows the message dialog and returns a promise that resolves to the
+    /// button that was clicked: `'Ok'`, `'Cancel'`, `'Yes'`, `'No'` or the
+    /// label of a custom button. Closing the dialog resolves to `'Cancel'`.
+    ///
+    /// The builder is left as-is, so calling `.show()` again shows the same
+    /// dialog again.
     ///
     /// Example:
     ///
     /// ```js
     /// const answer = await new AsyncMessageDialog()
-    ///     .setButtons('Ok')
+    ///     .setButtons('OkCancel')
     ///     .show();
     /// console.log(answer);
-    /// //=> true
+    /// //=> 'Cancel'
     /// ```
-    #[napi]
-    pub async unsafe fn show(&mut self) -> Result<bool> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let y = x.show().await;
-        return Ok(y);
+    #[napi(ts_return_type = "Promise<MessageDialogResult | string>")]
+    pub async unsafe fn show(&self, options: Option<AbortOptions>) -> AbortableResult<String> {
+        let x = self.0.clone();
+        let y = backend::async_message_dialog(x.clone(), abort_token(&options)).await;
+        return AbortableResult(y.map(|y| x.response(y).result), options);
+    }
+
+    /// Same as `.show()` but also says whether the dialog timed out. See
+    /// `.setTimeout()`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const { result, timedOut } = await new AsyncMessageDialog()
+    ///     .setTimeout(10_000, 'Ok')
+    ///     .showDetailed();
+    /// ```
+    #[napi(ts_return_type = "Promise<MessageDialogResponse>")]
+    pub async unsafe fn show_detailed(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<MessageDialogResponse> {
+        let x = self.0.clone();
+        let y = backend::async_message_dialog(x.clone(), abort_token(&options)).await;
+        return AbortableResult(y.map(|y| x.response(y)), options);
     }
 }
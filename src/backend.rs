@@ -0,0 +1,371 @@
+use super::abort::*;
+use super::backend_name::*;
+use super::color_dialog::*;
+use super::error::*;
+use super::file_dialog::*;
+use super::font_dialog::*;
+use super::input_dialog::*;
+use super::message_dialog::*;
+use super::message_dialog_result::*;
+use super::mock;
+use super::notification::*;
+#[cfg(freedesktop)]
+use super::notifications;
+#[cfg(freedesktop)]
+use super::portal;
+use super::subprocess::{self, Tool};
+use napi::bindgen_prelude::block_on;
+use napi::tokio;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Which implementation actually shows the dialogs. Every dialog builder goes
+/// through the functions in this module instead of calling `rfd` directly so
+/// that the implementation can be swapped out at runtime.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Backend {
+    /// The native OS dialogs provided by `rfd`.
+    Native,
+    /// The scriptable headless backend from the `mock` namespace.
+    Mock,
+    /// Runs `zenity` as a subprocess.
+    Zenity,
+    /// Runs `kdialog` as a subprocess.
+    Kdialog,
+    /// Talks to the XDG desktop portal over D-Bus.
+    Portal,
+}
+impl Backend {
+    /// The backend picked by the `RFD_BACKEND` environment variable. This is
+    /// read every time so that it can be changed with `process.env` at runtime.
+    fn from_env() -> Self {
+        match env::var("RFD_BACKEND").as_deref() {
+            Ok("zenity") => Backend::Zenity,
+            Ok("kdialog") => Backend::Kdialog,
+            Ok("portal") => Backend::Portal,
+            _ => Backend::Native,
+        }
+    }
+
+    pub(crate) fn name(&self) -> BackendName {
+        match self {
+            Backend::Native => BackendName::native(),
+            Backend::Mock => BackendName::Mock,
+            Backend::Zenity => BackendName::Zenity,
+            Backend::Kdialog => BackendName::Kdialog,
+            Backend::Portal => BackendName::Portal,
+        }
+    }
+
+    fn tool(&self) -> Option<Tool> {
+        match self {
+            Backend::Zenity => Some(Tool::Zenity),
+            Backend::Kdialog => Some(Tool::Kdialog),
+            _ => None,
+        }
+    }
+}
+
+/// The backend that was explicitly chosen. `None` falls back to
+/// `Backend::from_env()`.
+static BACKEND: Mutex<Option<Backend>> = Mutex::new(None);
+
+pub(crate) fn get() -> Backend {
+    let backend = *BACKEND.lock().unwrap();
+    return backend.unwrap_or_else(Backend::from_env);
+}
+
+pub(crate) fn set(backend: Option<Backend>) {
+    *BACKEND.lock().unwrap() = backend;
+}
+
+/// Runs a blocking subprocess dialog on a worker thread so that it doesn't hold
+/// up the async runtime.
+async fn spawn_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> RfdResult<T> + Send + 'static,
+) -> RfdResult<T> {
+    let result = tokio::task::spawn_blocking(f).await;
+    if let Err(error) = result {
+        return Err(RfdError::new(ErrorCode::BackendUnavailable, error));
+    }
+    return result.unwrap();
+}
+
+async fn portal_file_dialog(
+    method: FileMethod,
+    options: &FileOptions,
+    abort: &AbortToken,
+) -> RfdResult<Option<Vec<PathBuf>>> {
+    #[cfg(freedesktop)]
+    return portal::file_dialog(method, options, abort).await;
+    #[cfg(not(freedesktop))]
+    return Err(RfdError::new(
+        ErrorCode::BackendUnavailable,
+        "The XDG desktop portal is only available on Linux and the BSDs",
+    ));
+}
+
+/// Only the portal has a dialog for saving several files at once. Everywhere
+/// else `.saveFiles()` asks for a folder and puts the files in there.
+fn save_files_in_folder(folder: Option<PathBuf>, options: &FileOptions) -> Option<Vec<PathBuf>> {
+    return folder.map(|x| options.file_names.iter().map(|name| x.join(name)).collect());
+}
+
+pub(crate) fn file_dialogs(
+    method: FileMethod,
+    options: &FileOptions,
+) -> RfdResult<Option<Vec<PathBuf>>> {
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::file_dialog(method, options);
+    }
+    if backend == Backend::Portal {
+        let abort = AbortToken::default();
+        return block_on(portal_file_dialog(method, options, &abort));
+    }
+    if let FileMethod::SaveFiles = method {
+        let folder = file_dialog(FileMethod::PickFolder, options)?;
+        return Ok(save_files_in_folder(folder, options));
+    }
+    if let Some(tool) = backend.tool() {
+        return subprocess::file_dialog(tool, method, options, &AbortToken::default());
+    }
+    let x = options.to_rfd_t();
+    let path_bufs = match method {
+        FileMethod::PickFile => x.pick_file().map(|x| vec![x]),
+        FileMethod::PickFiles => x.pick_files(),
+        FileMethod::PickFolder => x.pick_folder().map(|x| vec![x]),
+        FileMethod::PickFolders => x.pick_folders(),
+        FileMethod::SaveFile => x.save_file().map(|x| vec![x]),
+        // Handled by `save_files_in_folder()` above.
+        FileMethod::SaveFiles => x.pick_folder().map(|x| vec![x]),
+    };
+    return Ok(path_bufs);
+}
+
+pub(crate) fn file_dialog(method: FileMethod, options: &FileOptions) -> RfdResult<Option<PathBuf>> {
+    let path_bufs = file_dialogs(method, options)?;
+    return Ok(path_bufs.and_then(|x| x.into_iter().next()));
+}
+
+/// Shows a file dialog without blocking the async runtime. The dialog rejects
+/// with an `AbortError` once `abort` is aborted. The subprocess and portal
+/// backends close their dialog at that point, `rfd` can't so its dialog stays
+/// open and the answer is thrown away.
+pub(crate) async fn async_file_dialogs(
+    method: FileMethod,
+    options: FileOptions,
+    abort: AbortToken,
+) -> RfdResult<Option<Vec<PathBuf>>> {
+    if abort.is_aborted() {
+        return Err(RfdError::aborted());
+    }
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::file_dialog(method, &options);
+    }
+    if backend == Backend::Portal {
+        return portal_file_dialog(method, &options, &abort).await;
+    }
+    if let FileMethod::SaveFiles = method {
+        let pick_folder = async_file_dialog(FileMethod::PickFolder, options.clone(), abort);
+        let folder = Box::pin(pick_folder).await?;
+        return Ok(save_files_in_folder(folder, &options));
+    }
+    if let Some(tool) = backend.tool() {
+        return spawn_blocking(move || subprocess::file_dialog(tool, method, &options, &abort))
+            .await;
+    }
+    let x = options.to_rfd_async_t();
+    let handles = abort
+        .race(async {
+            match method {
+                FileMethod::PickFile => x.pick_file().await.map(|x| vec![x]),
+                FileMethod::PickFiles => x.pick_files().await,
+                FileMethod::PickFolder => x.pick_folder().await.map(|x| vec![x]),
+                FileMethod::PickFolders => x.pick_folders().await,
+                FileMethod::SaveFile => x.save_file().await.map(|x| vec![x]),
+                FileMethod::SaveFiles => x.pick_folder().await.map(|x| vec![x]),
+            }
+        })
+        .await?;
+    let path_bufs = handles.map(|x| x.iter().map(|x| x.path().to_path_buf()).collect());
+    return Ok(path_bufs);
+}
+
+pub(crate) async fn async_file_dialog(
+    method: FileMethod,
+    options: FileOptions,
+    abort: AbortToken,
+) -> RfdResult<Option<PathBuf>> {
+    let path_bufs = async_file_dialogs(method, options, abort).await?;
+    return Ok(path_bufs.and_then(|x| x.into_iter().next()));
+}
+
+/// Shows a message dialog. Returns `None` if the dialog timed out, which only
+/// the backends that can close their dialog support. `rfd`'s blocking dialogs
+/// can't be given up on without blocking the main thread anyway, so they wait
+/// for an answer.
+pub(crate) fn message_dialog(options: &MessageOptions) -> RfdResult<Option<String>> {
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::message_dialog(options);
+    }
+    if let Some(tool) = backend.tool() {
+        return subprocess::message_dialog(tool, options, &AbortToken::default());
+    }
+    return Ok(Some(result_from_rfd_t(options.to_rfd_t().show())));
+}
+
+/// Like `async_file_dialogs()`, `abort` rejects with an `AbortError`. `rfd`'s
+/// dialogs can't be closed, so once the timeout passes, they return `None`
+/// like the other backends and the dialog stays open with its answer thrown
+/// away.
+pub(crate) async fn async_message_dialog(
+    options: MessageOptions,
+    abort: AbortToken,
+) -> RfdResult<Option<String>> {
+    if abort.is_aborted() {
+        return Err(RfdError::aborted());
+    }
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::message_dialog(&options);
+    }
+    if let Some(tool) = backend.tool() {
+        return spawn_blocking(move || subprocess::message_dialog(tool, &options, &abort)).await;
+    }
+    let show = options.to_rfd_async_t().show();
+    if let Some((ms, _)) = &options.timeout {
+        let result = tokio::time::timeout(Duration::from_millis(*ms as u64), show);
+        return Ok(abort.race(result).await?.ok().map(result_from_rfd_t));
+    }
+    let result = abort.race(show).await?;
+    return Ok(Some(result_from_rfd_t(result)));
+}
+
+/// `rfd` only has file and message dialogs, so the native and portal backends
+/// show the other kinds of dialogs with `zenity` where it can exist. `what`
+/// names the kind of dialog for the error message.
+fn fallback_tool(backend: Backend, what: &str) -> RfdResult<Tool> {
+    if let Some(tool) = backend.tool() {
+        return Ok(tool);
+    }
+    if cfg!(freedesktop) {
+        return Ok(Tool::Zenity);
+    }
+    return Err(RfdError::new(
+        ErrorCode::BackendUnavailable,
+        format!("{} are only available on Linux and the BSDs", what),
+    ));
+}
+
+/// Shows a text input dialog. Returns `None` if it was cancelled.
+pub(crate) fn input_dialog(options: &InputOptions) -> RfdResult<Option<String>> {
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::input_dialog(options);
+    }
+    let tool = fallback_tool(backend, "Input dialogs")?;
+    return subprocess::input_dialog(tool, options, &AbortToken::default());
+}
+
+/// Like `async_file_dialogs()`, `abort` rejects with an `AbortError`.
+pub(crate) async fn async_input_dialog(
+    options: InputOptions,
+    abort: AbortToken,
+) -> RfdResult<Option<String>> {
+    if abort.is_aborted() {
+        return Err(RfdError::aborted());
+    }
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::input_dialog(&options);
+    }
+    let tool = fallback_tool(backend, "Input dialogs")?;
+    return spawn_blocking(move || subprocess::input_dialog(tool, &options, &abort)).await;
+}
+
+/// Shows a color picker. Returns `None` if it was cancelled.
+pub(crate) fn color_dialog(options: &ColorOptions) -> RfdResult<Option<Rgba>> {
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::color_dialog(options);
+    }
+    let tool = fallback_tool(backend, "Color dialogs")?;
+    return subprocess::color_dialog(tool, options, &AbortToken::default());
+}
+
+/// Like `async_file_dialogs()`, `abort` rejects with an `AbortError`.
+pub(crate) async fn async_color_dialog(
+    options: ColorOptions,
+    abort: AbortToken,
+) -> RfdResult<Option<Rgba>> {
+    if abort.is_aborted() {
+        return Err(RfdError::aborted());
+    }
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::color_dialog(&options);
+    }
+    let tool = fallback_tool(backend, "Color dialogs")?;
+    return spawn_blocking(move || subprocess::color_dialog(tool, &options, &abort)).await;
+}
+
+/// Shows a font chooser. Returns `None` if it was cancelled. Only the mock
+/// backend has one since neither `rfd` nor the tools can show it.
+pub(crate) fn font_dialog(options: &FontOptions) -> RfdResult<Option<Font>> {
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::font_dialog(options);
+    }
+    return Err(font_dialog_unavailable(backend));
+}
+
+/// Like `async_file_dialogs()`, `abort` rejects with an `AbortError`.
+pub(crate) async fn async_font_dialog(
+    options: FontOptions,
+    abort: AbortToken,
+) -> RfdResult<Option<Font>> {
+    if abort.is_aborted() {
+        return Err(RfdError::aborted());
+    }
+    let backend = get();
+    if backend == Backend::Mock {
+        return mock::font_dialog(&options);
+    }
+    return Err(font_dialog_unavailable(backend));
+}
+
+fn font_dialog_unavailable(backend: Backend) -> RfdError {
+    return RfdError::new(
+        ErrorCode::BackendUnavailable,
+        format!(
+            "Font dialogs aren't supported by the '{}' backend",
+            backend.name().as_ref()
+        ),
+    );
+}
+
+/// Sends a desktop notification. Every backend but the mock one sends it to the
+/// notification server, since neither `rfd` nor the tools have a way to.
+pub(crate) async fn async_notification(
+    options: NotificationOptions,
+    abort: AbortToken,
+) -> RfdResult<Option<String>> {
+    if abort.is_aborted() {
+        return Err(RfdError::aborted());
+    }
+    if get() == Backend::Mock {
+        return mock::notification(&options);
+    }
+    #[cfg(freedesktop)]
+    return notifications::notify(&options, &abort).await;
+    #[cfg(not(freedesktop))]
+    return Err(RfdError::new(
+        ErrorCode::BackendUnavailable,
+        "Notifications are only available on Linux and the BSDs",
+    ));
+}
@@ -0,0 +1,138 @@
+use super::backend::{self, Backend};
+use super::error::*;
+use napi::bindgen_prelude::*;
+
+/// The implementation that shows the dialogs. `'gtk'`, `'windows'` and
+/// `'macos'` are the native dialogs of the platform that this package was built
+/// for, so only one of them is ever available. The others can be picked with
+/// `setBackend()` or the `RFD_BACKEND` environment variable.
+///
+/// Example:
+///
+/// ```js
+/// if (getBackend() === BackendName.Gtk) {
+///   setBackend(BackendName.Portal);
+/// }
+/// ```
+#[napi(string_enum)]
+#[derive(PartialEq)]
+pub enum BackendName {
+    /// GTK 3 dialogs on Linux and the BSDs.
+    #[napi(value = "gtk")]
+    Gtk,
+    /// The XDG desktop portal over D-Bus, for Flatpak and other sandboxes.
+    #[napi(value = "portal")]
+    Portal,
+    /// The Win32 common dialogs.
+    #[napi(value = "windows")]
+    Windows,
+    /// `NSOpenPanel`, `NSSavePanel` and `NSAlert`.
+    #[napi(value = "macos")]
+    MacOs,
+    /// GNOME's `zenity`, run as a subprocess.
+    #[napi(value = "zenity")]
+    Zenity,
+    /// KDE's `kdialog`, run as a subprocess.
+    #[napi(value = "kdialog")]
+    Kdialog,
+    /// The headless backend from the `mock` namespace.
+    #[napi(value = "mock")]
+    Mock,
+}
+impl AsRef<str> for BackendName {
+    fn as_ref(&self) -> &str {
+        match self {
+            BackendName::Gtk => "gtk",
+            BackendName::Portal => "portal",
+            BackendName::Windows => "windows",
+            BackendName::MacOs => "macos",
+            BackendName::Zenity => "zenity",
+            BackendName::Kdialog => "kdialog",
+            BackendName::Mock => "mock",
+        }
+    }
+}
+impl BackendName {
+    /// The name of the native dialogs that this package was built with. Builds
+    /// without the `gtk3` Cargo feature use `rfd`'s own portal client.
+    pub(crate) fn native() -> Self {
+        if cfg!(target_os = "windows") {
+            return BackendName::Windows;
+        }
+        if cfg!(target_os = "macos") {
+            return BackendName::MacOs;
+        }
+        if cfg!(feature = "gtk3") {
+            return BackendName::Gtk;
+        }
+        return BackendName::Portal;
+    }
+
+    pub(crate) fn to_backend(self) -> RfdResult<Backend> {
+        let backend = match self {
+            BackendName::Gtk | BackendName::Windows | BackendName::MacOs => {
+                if self != BackendName::native() {
+                    return Err(RfdError::new(
+                        ErrorCode::BackendUnavailable,
+                        format!(
+                            "The '{}' backend isn't available in this build",
+                            self.as_ref()
+                        ),
+                    ));
+                }
+                Backend::Native
+            }
+            BackendName::Portal => {
+                if !cfg!(freedesktop) {
+                    return Err(RfdError::new(
+                        ErrorCode::BackendUnavailable,
+                        "The XDG desktop portal is only available on Linux and the BSDs",
+                    ));
+                }
+                Backend::Portal
+            }
+            BackendName::Zenity => Backend::Zenity,
+            BackendName::Kdialog => Backend::Kdialog,
+            BackendName::Mock => Backend::Mock,
+        };
+        return Ok(backend);
+    }
+}
+
+/// Returns the name of the backend that the next dialog will use. This takes
+/// `setBackend()`, `mock.install()` and the `RFD_BACKEND` environment variable
+/// into account.
+///
+/// Example:
+///
+/// ```js
+/// console.log(getBackend());
+/// //=> 'gtk'
+/// ```
+#[napi]
+pub fn get_backend() -> BackendName {
+    return backend::get().name();
+}
+
+/// Picks the backend for every dialog from now on. Pass `null` to go back to
+/// the default, which is the native dialogs unless the `RFD_BACKEND`
+/// environment variable says otherwise. Throws `ERR_BACKEND_UNAVAILABLE` for
+/// the native backends of other platforms, like `'windows'` on Linux.
+///
+/// Example:
+///
+/// ```js
+/// setBackend('zenity');
+/// const path = new FileDialog().pickFile();
+/// setBackend(null);
+/// ```
+#[napi]
+pub fn set_backend(name: Option<BackendName>) -> Result<(), ErrorCode> {
+    if name.is_none() {
+        backend::set(None);
+        return Ok(());
+    }
+    let backend = name.unwrap().to_backend()?;
+    backend::set(Some(backend));
+    return Ok(());
+}
@@ -0,0 +1,193 @@
+use super::backend::{self, Backend};
+use super::backend_name::*;
+use super::error::*;
+use napi::bindgen_prelude::*;
+
+/// Which `FileDialog` and `AsyncFileDialog` features a backend honours. Options
+/// that aren't supported are silently ignored, except for methods like
+/// `.pickFolders()` which reject with `ERR_BACKEND_UNAVAILABLE`.
+#[napi(object)]
+pub struct FileDialogCapabilities {
+    /// `.addFilter()` limits which files can be picked.
+    pub filters: bool,
+    /// `.setDirectory()` picks the folder that the dialog starts in.
+    pub directory: bool,
+    /// `.setFileName()` fills in the file name.
+    pub file_name: bool,
+    /// `.setTitle()` changes the window title.
+    pub title: bool,
+    /// `.setParent()` makes the dialog modal for that window.
+    pub parent: bool,
+    /// `.pickFiles()` lets the user select more than one file.
+    pub pick_files: bool,
+    /// `.pickFolders()` lets the user select more than one folder.
+    pub pick_folders: bool,
+    /// `.saveFiles()` shows a dedicated dialog instead of asking for a folder.
+    pub save_files: bool,
+}
+
+/// Which `MessageDialog` and `AsyncMessageDialog` features a backend honours.
+#[napi(object)]
+pub struct MessageDialogCapabilities {
+    /// `.setTitle()` changes the window title.
+    pub title: bool,
+    /// `.setLevel()` changes the icon.
+    pub level: bool,
+    /// `.setParent()` makes the dialog modal for that window.
+    pub parent: bool,
+    /// The `...Custom` buttons show their labels instead of the default ones.
+    pub custom_buttons: bool,
+    /// `.setTimeout()` closes the dialog once the time is up. Without it,
+    /// `AsyncMessageDialog` still resolves on time, but `MessageDialog` waits
+    /// for an answer.
+    pub timeout: bool,
+}
+
+/// Which `InputDialog` and `AsyncInputDialog` features a backend honours.
+#[napi(object)]
+pub struct InputDialogCapabilities {
+    /// The dialog can be shown at all. `rfd` has no input dialog, so the
+    /// native backends run `zenity` for it on Linux and the BSDs and throw
+    /// `ERR_BACKEND_UNAVAILABLE` elsewhere.
+    pub available: bool,
+}
+
+/// Which `ColorDialog` and `AsyncColorDialog` features a backend honours.
+#[napi(object)]
+pub struct ColorDialogCapabilities {
+    /// The dialog can be shown at all. Like input dialogs, this needs `zenity`
+    /// for the native backends on Linux and the BSDs.
+    pub available: bool,
+    /// `.setAlpha()` lets the user pick the opacity.
+    pub alpha: bool,
+}
+
+/// Which `FontDialog` and `AsyncFontDialog` features a backend honours.
+#[napi(object)]
+pub struct FontDialogCapabilities {
+    /// The dialog can be shown at all. Neither `rfd` nor `zenity` and
+    /// `kdialog` have a font chooser, so only the `mock` backend does so far.
+    pub available: bool,
+}
+
+/// Which `Notification` features a backend honours.
+#[napi(object)]
+pub struct NotificationCapabilities {
+    /// Notifications can be sent at all. They go to the notification server
+    /// over D-Bus no matter the backend, so only Linux and the BSDs have them.
+    pub available: bool,
+}
+
+/// What `capabilities()` returns.
+#[napi(object)]
+pub struct BackendCapabilities {
+    pub backend: BackendName,
+    pub file_dialog: FileDialogCapabilities,
+    pub message_dialog: MessageDialogCapabilities,
+    pub input_dialog: InputDialogCapabilities,
+    pub color_dialog: ColorDialogCapabilities,
+    pub font_dialog: FontDialogCapabilities,
+    pub notification: NotificationCapabilities,
+}
+
+/// Describes what a backend can do so that an app can hide features that
+/// wouldn't work instead of finding out from an error. Describes the backend
+/// that `getBackend()` returns unless another one is named. Throws
+/// `ERR_BACKEND_UNAVAILABLE` for backends that `setBackend()` would reject.
+///
+/// Example:
+///
+/// ```js
+/// const { fileDialog } = capabilities();
+/// const dialog = new AsyncFileDialog();
+/// const fileHandles = fileDialog.pickFolders
+///   ? await dialog.pickFolders()
+///   : [await dialog.pickFolder()];
+/// ```
+#[napi]
+pub fn capabilities(name: Option<BackendName>) -> Result<BackendCapabilities, ErrorCode> {
+    let backend = match name {
+        Some(name) => name.to_backend()?,
+        None => backend::get(),
+    };
+    let mut file_dialog = FileDialogCapabilities {
+        filters: true,
+        directory: true,
+        file_name: true,
+        title: true,
+        parent: true,
+        pick_files: true,
+        pick_folders: true,
+        save_files: true,
+    };
+    let mut message_dialog = MessageDialogCapabilities {
+        title: true,
+        level: true,
+        parent: true,
+        custom_buttons: true,
+        timeout: true,
+    };
+    let mut input_dialog = InputDialogCapabilities { available: true };
+    let mut color_dialog = ColorDialogCapabilities {
+        available: true,
+        alpha: true,
+    };
+    let font_dialog = FontDialogCapabilities {
+        available: backend == Backend::Mock,
+    };
+    let mut notification = NotificationCapabilities { available: true };
+    match backend.name() {
+        BackendName::Gtk => {
+            file_dialog.parent = false;
+            file_dialog.save_files = false;
+            message_dialog.parent = false;
+            message_dialog.timeout = false;
+        }
+        BackendName::Portal => {
+            // There is no message dialog portal, so those are shown by GTK
+            // (or by `rfd` with zenity in builds without GTK).
+            message_dialog.parent = false;
+            message_dialog.timeout = false;
+            if backend == Backend::Native {
+                // `rfd`'s own portal client.
+                file_dialog.parent = false;
+                file_dialog.save_files = false;
+            }
+        }
+        BackendName::Windows => {
+            file_dialog.save_files = false;
+            message_dialog.custom_buttons = false;
+            message_dialog.timeout = false;
+            notification.available = false;
+            input_dialog.available = false;
+            color_dialog.available = false;
+            color_dialog.alpha = false;
+        }
+        BackendName::MacOs => {
+            file_dialog.save_files = false;
+            message_dialog.timeout = false;
+            notification.available = false;
+            input_dialog.available = false;
+            color_dialog.available = false;
+            color_dialog.alpha = false;
+        }
+        BackendName::Zenity => {
+            file_dialog.save_files = false;
+        }
+        BackendName::Kdialog => {
+            file_dialog.pick_folders = false;
+            file_dialog.save_files = false;
+            color_dialog.alpha = false;
+        }
+        BackendName::Mock => {}
+    }
+    return Ok(BackendCapabilities {
+        backend: backend.name(),
+        file_dialog,
+        message_dialog,
+        input_dialog,
+        color_dialog,
+        font_dialog,
+        notification,
+    });
+}
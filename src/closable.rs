@@ -0,0 +1,46 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// The state of an object whose async methods do blocking I/O on worker
+/// threads, like the open file of a `FileStream`, while its `close()` or
+/// `cancel()` is called on the main thread. Closing only sets a flag and never
+/// waits for the lock, so that it can't block the event loop behind a slow
+/// read. The state is dropped right away if it's idle, or otherwise by the
+/// worker once its I/O is done.
+pub(crate) struct Closable<T> {
+    closed: AtomicBool,
+    state: Mutex<Option<T>>,
+}
+impl<T> Closable<T> {
+    pub(crate) fn new(state: T) -> Self {
+        return Self {
+            closed: AtomicBool::new(false),
+            state: Mutex::new(Some(state)),
+        };
+    }
+
+    /// Runs `f` with the state on a worker thread. Returns `None` if the
+    /// object was closed before or while `f` ran; the state is dropped then.
+    pub(crate) fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let mut state = self.state.lock().unwrap();
+        if self.closed.load(Ordering::SeqCst) || state.is_none() {
+            *state = None;
+            return None;
+        }
+        let result = f(state.as_mut().unwrap());
+        if self.closed.load(Ordering::SeqCst) {
+            *state = None;
+            return None;
+        }
+        return Some(result);
+    }
+
+    /// Closes the object from any thread without blocking. Calling it again
+    /// does nothing.
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        if let Ok(mut state) = self.state.try_lock() {
+            *state = None;
+        }
+    }
+}
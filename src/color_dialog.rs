@@ -0,0 +1,232 @@
+use super::backend;
+use super::error::*;
+use super::parent_window::*;
+use napi::bindgen_prelude::*;
+
+/// A color as red, green, blue and alpha channels.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct Rgba(pub(crate) [u8; 4]);
+impl Rgba {
+    /// Parses a CSS hex color: `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`. The
+    /// `#` is optional.
+    pub(crate) fn from_hex(hex: &str) -> RfdResult<Self> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let error = || {
+            return RfdError::new(
+                ErrorCode::InvalidArg,
+                format!("Expected a hex color like '#ff8800', got '{}'", hex),
+            );
+        };
+        if !digits.bytes().all(|x| x.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+        let channel = |i: usize, len: usize| {
+            let x = u8::from_str_radix(&digits[i * len..(i + 1) * len], 16).unwrap();
+            return if len == 1 { x * 0x11 } else { x };
+        };
+        let rgba = match digits.len() {
+            3 => [channel(0, 1), channel(1, 1), channel(2, 1), 0xff],
+            4 => [channel(0, 1), channel(1, 1), channel(2, 1), channel(3, 1)],
+            6 => [channel(0, 2), channel(1, 2), channel(2, 2), 0xff],
+            8 => [channel(0, 2), channel(1, 2), channel(2, 2), channel(3, 2)],
+            _ => return Err(error()),
+        };
+        return Ok(Self(rgba));
+    }
+
+    /// `#rrggbb`, or `#rrggbbaa` when `alpha` is set.
+    pub(crate) fn to_hex(self, alpha: bool) -> String {
+        let [r, g, b, a] = self.0;
+        if alpha {
+            return format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a);
+        }
+        return format!("#{:02x}{:02x}{:02x}", r, g, b);
+    }
+
+    pub(crate) fn to_js(self, alpha: bool) -> Color {
+        let [r, g, b, a] = self.0;
+        return Color {
+            r,
+            g,
+            b,
+            a: f64::from(a) / 255.0,
+            hex: self.to_hex(alpha),
+        };
+    }
+}
+
+/// The color that `ColorDialog.show()` and `AsyncColorDialog.show()` return.
+///
+/// Example:
+///
+/// ```js
+/// const color = new ColorDialog().setColor('#ff8800').show();
+/// console.log(color);
+/// //=> { r: 255, g: 136, b: 0, a: 1, hex: '#ff8800' }
+/// ```
+#[napi(object)]
+pub struct Color {
+    /// The red channel from 0 to 255.
+    pub r: u8,
+    /// The green channel from 0 to 255.
+    pub g: u8,
+    /// The blue channel from 0 to 255.
+    pub b: u8,
+    /// The opacity from 0 to 1. Always 1 unless `.setAlpha(true)` was used.
+    pub a: f64,
+    /// The color as `#rrggbb`, or `#rrggbbaa` with `.setAlpha(true)`.
+    pub hex: String,
+}
+
+/// The plain-data configuration of a color dialog. Shared by the
+/// `ColorDialog` and `AsyncColorDialog` builders.
+#[derive(Clone, Default)]
+pub(crate) struct ColorOptions {
+    pub(crate) title: Option<String>,
+    /// The color that is selected when the dialog opens.
+    pub(crate) color: Option<Rgba>,
+    /// Lets the user pick the opacity too.
+    pub(crate) alpha: bool,
+    pub(crate) parent: Option<ParentWindow>,
+}
+impl ColorOptions {
+    /// Drops the opacity that the user couldn't have picked.
+    pub(crate) fn result(&self, rgba: Rgba) -> Color {
+        let mut rgba = rgba;
+        if !self.alpha {
+            rgba.0[3] = 0xff;
+        }
+        return rgba.to_js(self.alpha);
+    }
+}
+
+/// The builder for a synchronous color picker. It's recommended to use the
+/// async `AsyncColorDialog` builder instead since it lets the Node.js event
+/// loop continue even while the dialog is open.
+///
+/// `rfd` has no color dialog, so on Linux and the BSDs this is shown by
+/// `zenity` (or `kdialog` with `RFD_BACKEND=kdialog`), which has to be
+/// installed. Other platforms throw `ERR_BACKEND_UNAVAILABLE`.
+///
+/// Example:
+///
+/// ```js
+/// const color = new ColorDialog()
+///     .setTitle('Highlight color')
+///     .setColor('#ffff00')
+///     .show();
+/// console.log(color?.hex);
+/// //=> '#ff8800'
+/// ```
+#[napi]
+#[repr(transparent)]
+pub struct ColorDialog(pub(super) ColorOptions);
+impl Default for ColorDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[napi]
+impl ColorDialog {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        return Self(ColorOptions::default());
+    }
+
+    /// Returns a copy of this color dialog with the same settings.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const pick = new ColorDialog().setAlpha(true);
+    /// const color = pick.clone().setColor('#00000080').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
+    }
+
+    /// Sets the title of the color dialog. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const color = new ColorDialog().setTitle('Text color').show();
+    /// ```
+    #[napi]
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Sets the color that is selected when the dialog opens, as a CSS hex
+    /// color like `'#f80'`, `'#ff8800'` or `'#ff880080'`. Throws an
+    /// `ERR_INVALID_ARG` error for anything else. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const color = new ColorDialog().setColor('#ff8800').show();
+    /// ```
+    #[napi]
+    pub fn set_color(&mut self, this: This, hex: String) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.color = Some(Rgba::from_hex(&hex)?);
+        return Ok(this);
+    }
+
+    /// Lets the user pick the opacity too. Defaults to `false`, in which case
+    /// the result is always opaque. `kdialog` can't show an opacity slider.
+    /// Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const { a } = new ColorDialog().setAlpha(true).show();
+    /// ```
+    #[napi]
+    pub fn set_alpha(&mut self, this: This, alpha: bool) -> This {
+        let x = &mut self.0;
+        x.alpha = alpha;
+        return this;
+    }
+
+    /// Makes the color dialog modal for a parent window. See
+    /// `MessageDialog.setParent()` for the accepted handles.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const color = new ColorDialog().setParent(handle).show();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
+    }
+
+    /// Shows the color dialog. Returns the picked color, or `null` if the
+    /// dialog was cancelled.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const color = new ColorDialog().show();
+    /// if (color) {
+    ///   console.log(color.r, color.g, color.b);
+    /// }
+    /// ```
+    #[napi(ts_return_type = "Color | null")]
+    pub fn show(&self) -> SyncResult<Option<Color>> {
+        let x = &self.0;
+        let y = backend::color_dialog(x);
+        return SyncResult(y.map(|y| y.map(|y| x.result(y))));
+    }
+}
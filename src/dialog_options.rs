@@ -0,0 +1,280 @@
+use super::abort::*;
+use super::backend;
+use super::error::*;
+use super::file_dialog::*;
+use super::file_handle::*;
+use super::message_buttons::*;
+use super::message_dialog::*;
+use super::message_level::*;
+use super::parent_window::*;
+use napi::bindgen_prelude::*;
+use std::path::PathBuf;
+
+/// The options object accepted by `openFile()`, `openFiles()`, `openFolder()`
+/// and `saveFile()`. Every field is optional and maps to the matching
+/// `AsyncFileDialog` setter. Apart from the `signal`, it's a plain object that
+/// can be serialized with `JSON.stringify()` and sent across process boundaries
+/// as-is.
+///
+/// Example:
+///
+/// ```js
+/// const options = {
+///   title: 'Open a picture',
+///   filters: [{ name: 'Images', extensions: ['png', 'jpg'] }],
+///   directory: '/home/username/Pictures',
+/// };
+/// const fileHandle = await openFile(options);
+/// ```
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
+pub struct FileDialogOptions {
+    pub title: Option<String>,
+    pub filters: Option<Vec<FileFilter>>,
+    pub directory: Option<String>,
+    /// The default file name. Only used by `saveFile()`.
+    pub file_name: Option<String>,
+    /// Lets the user select more than one item. `openFile()` and
+    /// `openFolder()` then resolve to an array just like `openFiles()`.
+    /// Ignored by `saveFile()`.
+    pub multiple: Option<bool>,
+    /// A native window handle to make the dialog modal for. See
+    /// `FileDialog.setParent()`.
+    pub parent: Option<Either3<i64, BigInt, String>>,
+    /// Closes the dialog and rejects with an `AbortError` when aborted. See
+    /// `AbortOptions`.
+    #[napi(ts_type = "AbortSignal")]
+    pub signal: Option<AbortListener>,
+}
+impl TryFrom<FileDialogOptions> for FileOptions {
+    type Error = RfdError;
+
+    fn try_from(options: FileDialogOptions) -> RfdResult<Self> {
+        return Ok(Self {
+            filters: options.filters.unwrap_or_default(),
+            directory: options.directory,
+            file_name: options.file_name,
+            title: options.title,
+            parent: options.parent.map(ParentWindow::from_js).transpose()?,
+            ..Default::default()
+        });
+    }
+}
+
+/// The options object accepted by `message()`. Every field is optional and maps
+/// to the matching `AsyncMessageDialog` setter.
+///
+/// Example:
+///
+/// ```js
+/// const answer = await message({
+///   level: 'Warning',
+///   title: 'Delete file?',
+///   description: 'This cannot be undone.',
+///   buttons: 'YesNo',
+/// });
+/// ```
+#[napi(object, object_to_js = false)]
+#[derive(Default)]
+pub struct MessageDialogOptions {
+    pub level: Option<MessageLevel>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub buttons: Option<MessageButtons>,
+    /// The labels for the `...Custom` buttons.
+    pub labels: Option<Vec<String>>,
+    /// A native window handle to make the dialog modal for. See
+    /// `MessageDialog.setParent()`.
+    pub parent: Option<Either3<i64, BigInt, String>>,
+    /// Closes the dialog after this many milliseconds without an answer and
+    /// resolves to `defaultResult`. See `MessageDialog.setTimeout()`.
+    pub timeout: Option<u32>,
+    /// What a timed out dialog resolves to. Defaults to `'Cancel'`.
+    #[napi(ts_type = "MessageDialogResult | string")]
+    pub default_result: Option<String>,
+    /// Closes the dialog and rejects with an `AbortError` when aborted. See
+    /// `AbortOptions`.
+    #[napi(ts_type = "AbortSignal")]
+    pub signal: Option<AbortListener>,
+}
+impl TryFrom<MessageDialogOptions> for MessageOptions {
+    type Error = RfdError;
+
+    fn try_from(options: MessageDialogOptions) -> RfdResult<Self> {
+        let labels = options.labels.unwrap_or_default();
+        options
+            .buttons
+            .unwrap_or(MessageButtons::Ok)
+            .check_labels(&labels)?;
+        return Ok(Self {
+            level: options.level,
+            title: options.title,
+            description: options.description,
+            buttons: options.buttons,
+            labels,
+            parent: options.parent.map(ParentWindow::from_js).transpose()?,
+            timeout: options.timeout.map(|ms| {
+                let default_result = options.default_result.unwrap_or("Cancel".to_string());
+                (ms, default_result)
+            }),
+        });
+    }
+}
+
+fn wrap_path_bufs(path_bufs: Vec<PathBuf>) -> Vec<FileHandle> {
+    return path_bufs
+        .into_iter()
+        .map(|path_buf| FileHandle(rfd::FileHandle::from(path_buf)))
+        .collect();
+}
+
+/// Moves the `signal` out of an options object so that the listener can be
+/// removed by `AbortableResult` once the dialog is done.
+fn take_signal(signal: &mut Option<AbortListener>) -> Option<AbortOptions> {
+    return Some(AbortOptions {
+        signal: signal.take(),
+    });
+}
+
+/// Shows the `single` or the `multiple` file dialog depending on the `multiple`
+/// option.
+async fn pick(
+    single: FileMethod,
+    multiple: FileMethod,
+    options: FileDialogOptions,
+    abort: AbortToken,
+) -> RfdResult<Option<Either<FileHandle, Vec<FileHandle>>>> {
+    let is_multiple = options.multiple.unwrap_or(false);
+    let options = FileOptions::try_from(options)?;
+    if is_multiple {
+        let path_bufs = backend::async_file_dialogs(multiple, options, abort).await?;
+        return Ok(path_bufs.map(|x| Either::B(wrap_path_bufs(x))));
+    }
+    let path_buf = backend::async_file_dialog(single, options, abort).await?;
+    return Ok(path_buf.map(|x| Either::A(FileHandle(rfd::FileHandle::from(x)))));
+}
+
+/// Shows an open file dialog configured by a plain options object. This is the
+/// same as `new AsyncFileDialog()` with the matching setters followed by
+/// `.pickFile()`, or `.pickFiles()` when `multiple` is `true`.
+///
+/// Example:
+///
+/// ```js
+/// const fileHandle = await openFile({
+///   title: 'Open a text file',
+///   filters: [{ name: 'Text', extensions: ['txt'] }],
+/// });
+/// console.log(fileHandle?.path());
+/// //=> '/home/username/Documents/hello.txt'
+/// ```
+#[napi(ts_return_type = "Promise<FileHandle | Array<FileHandle> | null>")]
+pub async fn open_file(
+    options: Option<FileDialogOptions>,
+) -> AbortableResult<Option<Either<FileHandle, Vec<FileHandle>>>> {
+    let mut options = options.unwrap_or_default();
+    let abort = take_signal(&mut options.signal);
+    let token = abort_token(&abort);
+    let result = pick(FileMethod::PickFile, FileMethod::PickFiles, options, token).await;
+    return AbortableResult(result, abort);
+}
+
+/// Same as `openFile()` with `multiple: true`. Always resolves to an array of
+/// `FileHandle` instances or `null` if the user cancelled.
+///
+/// Example:
+///
+/// ```js
+/// const fileHandles = await openFiles({ directory: '/home/username/Pictures' });
+/// console.log(fileHandles.map((x) => x.path()));
+/// //=> ['/home/username/Pictures/a.png', '/home/username/Pictures/b.png']
+/// ```
+#[napi(ts_return_type = "Promise<Array<FileHandle> | null>")]
+pub async fn open_files(
+    options: Option<FileDialogOptions>,
+) -> AbortableResult<Option<Vec<FileHandle>>> {
+    let mut options = options.unwrap_or_default();
+    let abort = take_signal(&mut options.signal);
+    let options = match FileOptions::try_from(options) {
+        Ok(options) => options,
+        Err(error) => return AbortableResult(Err(error), abort),
+    };
+    let token = abort_token(&abort);
+    let path_bufs = backend::async_file_dialogs(FileMethod::PickFiles, options, token).await;
+    return AbortableResult(path_bufs.map(|x| x.map(wrap_path_bufs)), abort);
+}
+
+/// Shows a folder picker configured by a plain options object. Resolves to an
+/// array when `multiple` is `true`.
+///
+/// Example:
+///
+/// ```js
+/// const fileHandle = await openFolder({ title: 'Pick a project folder' });
+/// console.log(fileHandle?.path());
+/// //=> '/home/username/Projects/hello-world'
+/// ```
+#[napi(ts_return_type = "Promise<FileHandle | Array<FileHandle> | null>")]
+pub async fn open_folder(
+    options: Option<FileDialogOptions>,
+) -> AbortableResult<Option<Either<FileHandle, Vec<FileHandle>>>> {
+    let mut options = options.unwrap_or_default();
+    let abort = take_signal(&mut options.signal);
+    let token = abort_token(&abort);
+    let result = pick(
+        FileMethod::PickFolder,
+        FileMethod::PickFolders,
+        options,
+        token,
+    )
+    .await;
+    return AbortableResult(result, abort);
+}
+
+/// Shows a save file dialog configured by a plain options object. Resolves to
+/// the `FileHandle` the user chose or `null` if they cancelled.
+///
+/// Example:
+///
+/// ```js
+/// const fileHandle = await saveFile({ fileName: 'hello.txt' });
+/// await fileHandle?.write(Buffer.from('Hello, world!'));
+/// ```
+#[napi(ts_return_type = "Promise<FileHandle | null>")]
+pub async fn save_file(options: Option<FileDialogOptions>) -> AbortableResult<Option<FileHandle>> {
+    let mut options = options.unwrap_or_default();
+    let abort = take_signal(&mut options.signal);
+    let options = match FileOptions::try_from(options) {
+        Ok(options) => options,
+        Err(error) => return AbortableResult(Err(error), abort),
+    };
+    let token = abort_token(&abort);
+    let path_buf = backend::async_file_dialog(FileMethod::SaveFile, options, token).await;
+    let file_handle = path_buf.map(|x| x.map(|x| FileHandle(rfd::FileHandle::from(x))));
+    return AbortableResult(file_handle, abort);
+}
+
+/// Shows a message dialog configured by a plain options object. Resolves to
+/// the button that was clicked, just like `AsyncMessageDialog.show()`.
+///
+/// Example:
+///
+/// ```js
+/// const answer = await message({
+///   title: 'Quit?',
+///   description: 'Unsaved changes will be lost.',
+///   buttons: 'OkCancel',
+/// });
+/// ```
+#[napi(ts_return_type = "Promise<MessageDialogResult | string>")]
+pub async fn message(options: Option<MessageDialogOptions>) -> AbortableResult<String> {
+    let mut options = options.unwrap_or_default();
+    let abort = take_signal(&mut options.signal);
+    let options = match MessageOptions::try_from(options) {
+        Ok(options) => options,
+        Err(error) => return AbortableResult(Err(error), abort),
+    };
+    let token = abort_token(&abort);
+    let result = backend::async_message_dialog(options.clone(), token).await;
+    return AbortableResult(result.map(|x| options.response(x).result), abort);
+}
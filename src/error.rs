@@ -0,0 +1,238 @@
+use napi::bindgen_prelude::*;
+use napi::{tokio, JsError, JsObject};
+use std::io;
+use std::path::Path;
+
+/// The stable `code` property of every error thrown (or rejected) by this
+/// package. Match on this instead of on the error message, which may change
+/// between versions.
+///
+/// `ERR_IO` errors also have a `kind` property with the name of the Rust
+/// `std::io::ErrorKind` (like `'NotFound'` or `'PermissionDenied'`), an `errno`
+/// property with the raw OS error number when there is one, plus `syscall` and
+/// `path` properties describing what failed, similar to the errors from
+/// `node:fs`. Dialogs that were aborted through an `AbortSignal` reject with an
+/// error whose `name` is `'AbortError'` and whose `code` is `ERR_CANCELLED`.
+/// `ERR_INVALID_DATA` errors from `FileHandle.json()` have the 1-based `line`
+/// and `column` of the mistake.
+///
+/// Example:
+///
+/// ```js
+/// try {
+///   await FileHandle.wrap('/does/not/exist/hello.txt').write(Buffer.from('Hi'));
+/// } catch (error) {
+///   if (error.code === ErrorCode.Io) {
+///     console.log(error.kind, error.errno, error.syscall, error.path);
+///     //=> 'NotFound' 2 'open' '/does/not/exist/hello.txt'
+///   }
+/// }
+/// ```
+#[napi(string_enum)]
+#[derive(Debug)]
+pub enum ErrorCode {
+    /// A path couldn't be represented as a UTF-8 JavaScript string.
+    #[napi(value = "ERR_NON_UTF8_PATH")]
+    NonUtf8Path,
+    /// Reading from or writing to the file system failed.
+    #[napi(value = "ERR_IO")]
+    Io,
+    /// The dialog backend can't show this dialog right now.
+    #[napi(value = "ERR_BACKEND_UNAVAILABLE")]
+    BackendUnavailable,
+    /// The operation was cancelled before it finished.
+    #[napi(value = "ERR_CANCELLED")]
+    Cancelled,
+    /// An argument had an invalid value.
+    #[napi(value = "ERR_INVALID_ARG")]
+    InvalidArg,
+    /// The contents of a file couldn't be parsed.
+    #[napi(value = "ERR_INVALID_DATA")]
+    InvalidData,
+}
+impl AsRef<str> for ErrorCode {
+    fn as_ref(&self) -> &str {
+        match self {
+            ErrorCode::NonUtf8Path => "ERR_NON_UTF8_PATH",
+            ErrorCode::Io => "ERR_IO",
+            ErrorCode::BackendUnavailable => "ERR_BACKEND_UNAVAILABLE",
+            ErrorCode::Cancelled => "ERR_CANCELLED",
+            ErrorCode::InvalidArg => "ERR_INVALID_ARG",
+            ErrorCode::InvalidData => "ERR_INVALID_DATA",
+        }
+    }
+}
+
+/// The internal error type shared by every class. Methods return it through
+/// `AsyncResult` or `SyncResult` so that the extra `kind`, `errno`, `syscall`
+/// and `path` properties end up on the JavaScript error. Setters that only
+/// throw `ERR_INVALID_ARG` convert it into an `Error<ErrorCode>` with `?`.
+#[derive(Debug)]
+pub(crate) struct RfdError {
+    pub(crate) code: ErrorCode,
+    /// Overrides the `name` of the JavaScript error, which is `'Error'`
+    /// otherwise.
+    pub(crate) name: Option<String>,
+    pub(crate) message: String,
+    /// Boxed since most errors have none of them, and every `RfdResult` would
+    /// be as big as all of them otherwise.
+    pub(crate) details: Option<Box<ErrorDetails>>,
+}
+
+/// The extra properties of an `RfdError`. See `ErrorCode`.
+#[derive(Debug, Default)]
+pub(crate) struct ErrorDetails {
+    pub(crate) kind: Option<String>,
+    pub(crate) errno: Option<i32>,
+    pub(crate) syscall: Option<String>,
+    pub(crate) path: Option<String>,
+    pub(crate) line: Option<u32>,
+    pub(crate) column: Option<u32>,
+}
+
+impl RfdError {
+    pub(crate) fn new<M: ToString>(code: ErrorCode, message: M) -> Self {
+        return Self {
+            code,
+            name: None,
+            message: message.to_string(),
+            details: None,
+        };
+    }
+
+    /// The extra properties, which are created on first use.
+    pub(crate) fn details_mut(&mut self) -> &mut ErrorDetails {
+        return self.details.get_or_insert_with(Default::default);
+    }
+
+    pub(crate) fn non_utf8_path(path: &Path) -> Self {
+        let mut error = Self::new(
+            ErrorCode::NonUtf8Path,
+            format!("Path is not valid UTF-8: {}", path.to_string_lossy()),
+        );
+        error.details_mut().path = Some(path.to_string_lossy().into_owned());
+        return error;
+    }
+
+    pub(crate) fn io(error: io::Error, syscall: &str, path: &Path) -> Self {
+        let path = path.to_string_lossy().into_owned();
+        return Self {
+            code: ErrorCode::Io,
+            name: None,
+            message: format!("{}, {} '{}'", error, syscall, path),
+            details: Some(Box::new(ErrorDetails {
+                kind: Some(format!("{:?}", error.kind())),
+                errno: error.raw_os_error(),
+                syscall: Some(syscall.to_string()),
+                path: Some(path),
+                ..Default::default()
+            })),
+        };
+    }
+
+    /// The rejection of a dialog whose `AbortSignal` was aborted. The `name`
+    /// matches the `AbortError` that `fetch()` and friends reject with.
+    pub(crate) fn aborted() -> Self {
+        let mut error = Self::new(ErrorCode::Cancelled, "The operation was aborted");
+        error.name = Some("AbortError".to_string());
+        return error;
+    }
+
+    /// Creates the JavaScript `Error` object with all the extra properties.
+    /// The returned `napi::Error` holds a reference to that object so napi-rs
+    /// throws (or rejects with) it as-is.
+    pub(crate) fn into_napi(self, env: Env) -> Error {
+        let name = self.name;
+        let details = *self.details.unwrap_or_default();
+        let error = Error::new(self.code, self.message);
+        let unknown = JsError::from(error).into_unknown(env);
+        let mut object: JsObject = match unknown.coerce_to_object() {
+            Ok(object) => object,
+            Err(error) => return error,
+        };
+        let result = (|| {
+            if let Some(name) = name {
+                object.set_named_property("name", name)?;
+            }
+            if let Some(kind) = details.kind {
+                object.set_named_property("kind", kind)?;
+            }
+            if let Some(errno) = details.errno {
+                object.set_named_property("errno", errno)?;
+            }
+            if let Some(syscall) = details.syscall {
+                object.set_named_property("syscall", syscall)?;
+            }
+            if let Some(path) = details.path {
+                object.set_named_property("path", path)?;
+            }
+            if let Some(line) = details.line {
+                object.set_named_property("line", line)?;
+            }
+            if let Some(column) = details.column {
+                object.set_named_property("column", column)?;
+            }
+            Ok(())
+        })();
+        if let Err(error) = result {
+            return error;
+        }
+        return Error::from(object.into_unknown());
+    }
+}
+impl From<RfdError> for Error<ErrorCode> {
+    fn from(error: RfdError) -> Self {
+        return Error::new(error.code, error.message);
+    }
+}
+
+pub(crate) type RfdResult<T> = std::result::Result<T, RfdError>;
+
+/// The return type of the `async` methods. It's resolved on the main thread
+/// where the `RfdError` can be turned into a proper JavaScript error object.
+/// Since napi-rs doesn't know how to name this type, every method returning it
+/// needs a `ts_return_type`.
+pub struct AsyncResult<T>(pub(crate) RfdResult<T>);
+impl<T> From<RfdResult<T>> for AsyncResult<T> {
+    fn from(result: RfdResult<T>) -> Self {
+        return Self(result);
+    }
+}
+impl<T: ToNapiValue> ToNapiValue for AsyncResult<T> {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+        match val.0 {
+            Ok(value) => T::to_napi_value(env, value),
+            Err(error) => Err(error.into_napi(Env::from_raw(env))),
+        }
+    }
+}
+
+/// The return type of the synchronous methods that can fail with more than a
+/// `code`, like the dialogs and `FileHandle.path()`. It throws the same error
+/// object that `AsyncResult` rejects with, where the `?` conversion into
+/// `Error<ErrorCode>` would lose the extra properties. Every method returning
+/// it needs a `ts_return_type` too.
+pub struct SyncResult<T>(pub(crate) RfdResult<T>);
+impl<T> From<RfdResult<T>> for SyncResult<T> {
+    fn from(result: RfdResult<T>) -> Self {
+        return Self(result);
+    }
+}
+impl<T: ToNapiValue> ToNapiValue for SyncResult<T> {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+        return AsyncResult::to_napi_value(env, AsyncResult(val.0));
+    }
+}
+
+/// Runs blocking file I/O on tokio's blocking pool. Done right in an `async`
+/// method, a slow mount or a FIFO without a writer would tie up one of the
+/// runtime's worker threads and with it every other pending promise.
+pub(crate) async fn spawn_io<T: Send + 'static>(
+    f: impl FnOnce() -> RfdResult<T> + Send + 'static,
+) -> RfdResult<T> {
+    let result = tokio::task::spawn_blocking(f).await;
+    if let Err(error) = result {
+        return Err(RfdError::new(ErrorCode::Io, error));
+    }
+    return result.unwrap();
+}
@@ -0,0 +1,169 @@
+use super::closable::Closable;
+use super::error::*;
+use napi::bindgen_prelude::*;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Reads up to `length` bytes from the current position of `file`. The result
+/// is shorter than `length` only at the end of the file.
+fn read_at_most(file: &mut File, length: u64, path: &Path) -> RfdResult<Buffer> {
+    let mut vec = Vec::with_capacity(length.min(64 * 1024 * 1024) as usize);
+    let result = file.take(length).read_to_end(&mut vec);
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "read", path));
+    }
+    return Ok(vec.into());
+}
+
+fn check_offset(offset: i64) -> RfdResult<u64> {
+    if offset < 0 {
+        return Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            format!("Offsets must not be negative, got {}", offset),
+        ));
+    }
+    return Ok(offset as u64);
+}
+
+/// Opens `path` and reads `length` bytes starting at `offset`. Used by
+/// `FileHandle.readRange()`.
+pub(crate) fn read_range(path: &Path, offset: i64, length: u32) -> RfdResult<Buffer> {
+    let offset = check_offset(offset)?;
+    let file = File::open(path);
+    if file.is_err() {
+        return Err(RfdError::io(file.unwrap_err(), "open", path));
+    }
+    let mut file = file.unwrap();
+    let result = file.seek(SeekFrom::Start(offset));
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "seek", path));
+    }
+    return read_at_most(&mut file, u64::from(length), path);
+}
+
+/// A file that stays open for random access. Get one from `FileHandle.open()`.
+/// Every `.read()` continues where the last one stopped, and `.seek()` jumps
+/// to another position. The reads happen on a worker thread. Call `.close()`
+/// once you're done; otherwise the file is closed when this object is garbage
+/// collected.
+///
+/// Example:
+///
+/// ```js
+/// const fileHandle = await new AsyncFileDialog().pickFile();
+/// const cursor = await fileHandle.open();
+/// try {
+///   const magic = await cursor.read(4);
+///   await cursor.seek(1024);
+///   const block = await cursor.read(512);
+/// } finally {
+///   cursor.close();
+/// }
+/// ```
+#[napi]
+pub struct FileCursor {
+    path: PathBuf,
+    file: Arc<Closable<File>>,
+}
+#[napi]
+impl FileCursor {
+    /// Opens `path` for reading. Used by `FileHandle.open()`.
+    pub(crate) fn open(path: PathBuf) -> RfdResult<Self> {
+        let file = File::open(&path);
+        if file.is_err() {
+            return Err(RfdError::io(file.unwrap_err(), "open", &path));
+        }
+        return Ok(Self {
+            path,
+            file: Arc::new(Closable::new(file.unwrap())),
+        });
+    }
+
+    fn closed(&self) -> RfdError {
+        return RfdError::new(
+            ErrorCode::InvalidArg,
+            format!("FileCursor is closed: '{}'", self.path.to_string_lossy()),
+        );
+    }
+
+    /// Reads up to `length` bytes from the current position and moves past
+    /// them. Resolves to a shorter `Buffer` near the end of the file and to an
+    /// empty one at the end.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const cursor = await fileHandle.open();
+    /// const header = await cursor.read(16);
+    /// ```
+    #[napi(ts_return_type = "Promise<Buffer>")]
+    pub async unsafe fn read(&self, length: u32) -> AsyncResult<Buffer> {
+        let file = self.file.clone();
+        let path = self.path.clone();
+        let result =
+            spawn_io(move || Ok(file.with(|file| read_at_most(file, u64::from(length), &path))))
+                .await;
+        return match result {
+            Ok(Some(result)) => AsyncResult(result),
+            Ok(None) => AsyncResult(Err(self.closed())),
+            Err(error) => AsyncResult(Err(error)),
+        };
+    }
+
+    /// Moves to the byte offset `position` from the start of the file and
+    /// resolves to it. Seeking past the end is allowed; reads there resolve
+    /// to an empty `Buffer`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const cursor = await fileHandle.open();
+    /// await cursor.seek(0x200);
+    /// const sector = await cursor.read(0x200);
+    /// ```
+    #[napi(ts_return_type = "Promise<number>")]
+    pub async unsafe fn seek(&self, position: i64) -> AsyncResult<i64> {
+        let position = check_offset(position);
+        if position.is_err() {
+            return AsyncResult(Err(position.unwrap_err()));
+        }
+        let position = position.unwrap();
+        let file = self.file.clone();
+        let path = self.path.clone();
+        let result = spawn_io(move || {
+            let result = file.with(|file| file.seek(SeekFrom::Start(position)));
+            if result.is_none() {
+                return Ok(None);
+            }
+            let result = result.unwrap();
+            if result.is_err() {
+                return Err(RfdError::io(result.unwrap_err(), "seek", &path));
+            }
+            return Ok(Some(result.unwrap() as i64));
+        })
+        .await;
+        return match result {
+            Ok(Some(position)) => AsyncResult(Ok(position)),
+            Ok(None) => AsyncResult(Err(self.closed())),
+            Err(error) => AsyncResult(Err(error)),
+        };
+    }
+
+    /// Closes the file. A pending `.read()` or `.seek()` and any further ones
+    /// reject with an `ERR_INVALID_ARG` error. It returns right away even
+    /// while a read is in progress; the file is closed once that read is done.
+    /// Calling it again does nothing.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const cursor = await fileHandle.open();
+    /// cursor.close();
+    /// ```
+    #[napi]
+    pub fn close(&self) {
+        self.file.close();
+    }
+}
@@ -1,10 +1,117 @@
+use super::backend;
+use super::error::*;
+use super::parent_window::*;
+use super::path_encoding::*;
 use napi::bindgen_prelude::*;
 use rfd;
 
+/// A named list of file extensions like `{ name: 'Images', extensions: ['png',
+/// 'jpg'] }`. This is what `.addFilter()` records on the file dialog builders.
+#[napi(object)]
+#[derive(Clone)]
+pub struct FileFilter {
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+/// The terminal method that was used to open a file dialog. This is shared by
+/// the `FileDialog` and `AsyncFileDialog` builders so that every backend can
+/// handle both of them the same way.
+#[derive(Clone, Copy)]
+pub(crate) enum FileMethod {
+    PickFile,
+    PickFiles,
+    PickFolder,
+    PickFolders,
+    SaveFile,
+    SaveFiles,
+}
+impl FileMethod {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            FileMethod::PickFile => "pickFile",
+            FileMethod::PickFiles => "pickFiles",
+            FileMethod::PickFolder => "pickFolder",
+            FileMethod::PickFolders => "pickFolders",
+            FileMethod::SaveFile => "saveFile",
+            FileMethod::SaveFiles => "saveFiles",
+        }
+    }
+}
+
+/// The plain-data configuration of a file dialog. The builders record
+/// everything here instead of in an `rfd` dialog so that non-native backends
+/// (like the mock one) can see what was asked for.
+#[derive(Clone, Default)]
+pub(crate) struct FileOptions {
+    pub(crate) filters: Vec<FileFilter>,
+    pub(crate) directory: Option<String>,
+    pub(crate) file_name: Option<String>,
+    pub(crate) title: Option<String>,
+    pub(crate) parent: Option<ParentWindow>,
+    /// The names passed to `.saveFiles()`.
+    pub(crate) file_names: Vec<String>,
+    /// Only used by the synchronous `FileDialog` since `AsyncFileDialog`
+    /// returns `FileHandle` instances which can produce either encoding.
+    pub(crate) path_encoding: Option<PathEncoding>,
+}
+impl FileOptions {
+    pub(crate) fn to_rfd_t(&self) -> rfd::FileDialog {
+        let mut x = rfd::FileDialog::new();
+        for filter in &self.filters {
+            // https://users.rust-lang.org/t/vec-string-to-str/12619/2
+            let ext: Vec<&str> = filter.extensions.iter().map(<_>::as_ref).collect();
+            x = x.add_filter(&filter.name, &ext);
+        }
+        if let Some(dir) = &self.directory {
+            x = x.set_directory(dir);
+        }
+        if let Some(name) = &self.file_name {
+            x = x.set_file_name(name);
+        }
+        if let Some(title) = &self.title {
+            x = x.set_title(title);
+        }
+        // `rfd` can't use Wayland handles, and its GTK dialogs ignore the
+        // parent altogether. See `setParent()`.
+        if let Some(parent) = self.parent.as_ref().filter(|x| x.is_raw()) {
+            x = x.set_parent(parent);
+        }
+        return x;
+    }
+
+    pub(crate) fn to_rfd_async_t(&self) -> rfd::AsyncFileDialog {
+        let mut x = rfd::AsyncFileDialog::new();
+        for filter in &self.filters {
+            let ext: Vec<&str> = filter.extensions.iter().map(<_>::as_ref).collect();
+            x = x.add_filter(&filter.name, &ext);
+        }
+        if let Some(dir) = &self.directory {
+            x = x.set_directory(dir);
+        }
+        if let Some(name) = &self.file_name {
+            x = x.set_file_name(name);
+        }
+        if let Some(title) = &self.title {
+            x = x.set_title(title);
+        }
+        // `rfd` can't use Wayland handles, and its GTK dialogs ignore the
+        // parent altogether. See `setParent()`.
+        if let Some(parent) = self.parent.as_ref().filter(|x| x.is_raw()) {
+            x = x.set_parent(parent);
+        }
+        return x;
+    }
+}
+
 /// Synchronous file dialog builder. Use this to show file open/save dialogs.
 /// It's recommended to use the `AsyncFileDialog` builder instead since it lets
 /// the Node.js event loop continue even while the dialog is open.
 ///
+/// The setters change the builder in place and return it for chaining. The
+/// builder isn't used up by `.pickFile()` and friends, so the same configured
+/// dialog can be shown again later.
+///
 /// Example:
 ///
 /// ```js
@@ -20,12 +127,33 @@ use rfd;
 /// ```
 #[napi]
 #[repr(transparent)]
-pub struct FileDialog(pub(super) Option<rfd::FileDialog>);
+pub struct FileDialog(pub(super) FileOptions);
+impl Default for FileDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
 #[napi]
 impl FileDialog {
     #[napi(constructor)]
     pub fn new() -> Self {
-        return Self(Some(rfd::FileDialog::new()));
+        return Self(FileOptions::default());
+    }
+
+    /// Returns a copy of this builder. Changing the copy doesn't affect the
+    /// original, which makes it easy to derive variations from a shared
+    /// template.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const openProject = new FileDialog().addFilter('Project', ['proj']);
+    /// const path = openProject.clone().setDirectory('/tmp').pickFile();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
     }
 
     /// Adds a filter to the file dialog. The filter consists of a name and a
@@ -43,15 +171,13 @@ impl FileDialog {
     /// //=> '/home/username/Documents/hello.txt'
     /// ```
     #[napi]
-    pub fn add_filter(&mut self, name: String, ext: Vec<String>) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        // https://users.rust-lang.org/t/vec-string-to-str/12619/2
-        let ext: Vec<&str> = ext.iter().map(<_>::as_ref).collect();
-        let x = x.add_filter(&name, &ext);
-        return Ok(Self(Some(x)));
+    pub fn add_filter(&mut self, this: This, name: String, ext: Vec<String>) -> This {
+        let x = &mut self.0;
+        x.filters.push(FileFilter {
+            name,
+            extensions: ext,
+        });
+        return this;
     }
 
     /// Sets the directory that the file dialog will open in. The default is
@@ -67,13 +193,10 @@ impl FileDialog {
     /// console.log(path);
     /// //=> '/home/username/Documents/hello.txt'
     #[napi]
-    pub fn set_directory(&mut self, dir: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_directory(&dir);
-        return Ok(Self(Some(x)));
+    pub fn set_directory(&mut self, this: This, dir: String) -> This {
+        let x = &mut self.0;
+        x.directory = Some(dir);
+        return this;
     }
 
     /// Sets the file name that the file dialog will default to. Useful to give
@@ -89,13 +212,10 @@ impl FileDialog {
     /// //=> '/home/username/Documents/hello.txt'
     /// ```
     #[napi]
-    pub fn set_file_name(&mut self, name: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_file_name(&name);
-        return Ok(Self(Some(x)));
+    pub fn set_file_name(&mut self, this: This, name: String) -> This {
+        let x = &mut self.0;
+        x.file_name = Some(name);
+        return this;
     }
 
     /// Sets the title of the file dialog. Defaults to an empty string. Returns
@@ -111,13 +231,67 @@ impl FileDialog {
     /// //=> '/home/username/Documents/hello.txt'
     /// ```
     #[napi]
-    pub fn set_title(&mut self, title: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_title(&title);
-        return Ok(Self(Some(x)));
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Makes the dialog modal for one of your own windows so that it stays on
+    /// top of it instead of popping up unowned. Pass the native window handle:
+    /// an X11 window id (number or bigint) on Linux, a Wayland surface handle
+    /// exported with `xdg-foreign` (string), an `HWND` on Windows or an
+    /// `NSWindow*` on macOS (bigint). Throws an `ERR_INVALID_ARG` error if the
+    /// handle can't be used on this platform.
+    ///
+    /// Not every backend can honour it, in which case the dialog opens
+    /// unowned: the `gtk` backend and builds without GTK (`rfd`'s own portal
+    /// client) ignore it, Wayland handles are only used by the `portal`
+    /// backend, and `zenity` and `kdialog` only take X11 window ids. Check
+    /// `capabilities().fileDialog.parent`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// // Electron
+    /// const handle = browserWindow.getNativeWindowHandle().readBigUInt64LE();
+    /// const path = new FileDialog()
+    ///     .setParent(handle)
+    ///     .pickFile();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
+    }
+
+    /// Sets how the picked paths are returned. The default is `'Utf8'` which
+    /// returns strings and throws an `ERR_NON_UTF8_PATH` error if the user
+    /// picked a path that isn't valid UTF-8 (like a Latin-1 file name on an
+    /// old Linux share). Use `'Buffer'` to get the raw bytes of every path
+    /// instead so that no selection is ever lost. These `Buffer` paths work
+    /// with all the `node:fs` functions.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const path = new FileDialog()
+    ///     .setPathEncoding('Buffer')
+    ///     .pickFile();
+    /// console.log(path);
+    /// //=> <Buffer 2f 68 6f 6d 65 2f ...>
+    /// const text = await readFile(path, 'utf8');
+    /// ```
+    #[napi]
+    pub fn set_path_encoding(&mut self, this: This, encoding: PathEncoding) -> This {
+        let x = &mut self.0;
+        x.path_encoding = Some(encoding);
+        return this;
     }
 
     /// Shows the file dialog and blocks the thread until the user closes it.
@@ -132,25 +306,9 @@ impl FileDialog {
     /// console.log(path);
     /// //=> '/home/username/Documents/hello.txt'
     /// ```
-    #[napi]
-    pub fn pick_file(&mut self) -> Result<Option<String>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let path_buf = x.pick_file();
-        if path_buf.is_none() {
-            return Ok(None);
-        }
-        let path_buf = path_buf.unwrap();
-        let path = path_buf.to_str();
-
-        if path.is_none() {
-            return Err(Error::from_reason("Invalid UTF-8"));
-        }
-        let path = path.unwrap();
-        let path = path.to_string();
-        return Ok(Some(path));
+    #[napi(ts_return_type = "string | Buffer | null")]
+    pub fn pick_file(&self) -> SyncResult<Option<Either<String, Buffer>>> {
+        return SyncResult(show_one(FileMethod::PickFile, &self.0));
     }
 
     /// Shows the file dialog and blocks the thread until the user closes it.
@@ -165,28 +323,9 @@ impl FileDialog {
     /// console.log(paths);
     /// //=> ['/home/username/Documents/hello.txt', ...]
     /// ```
-    #[napi]
-    pub fn pick_files(&mut self) -> Result<Option<Vec<String>>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let path_bufs = x.pick_files();
-        if path_bufs.is_none() {
-            return Ok(None);
-        }
-        let path_bufs = path_bufs.unwrap();
-        let mut paths = Vec::new();
-        for path_buf in path_bufs {
-            let path = path_buf.to_str();
-            if path.is_none() {
-                return Err(Error::from_reason("Invalid UTF-8"));
-            }
-            let path = path.unwrap();
-            let path = path.to_string();
-            paths.push(path);
-        }
-        return Ok(Some(paths));
+    #[napi(ts_return_type = "Array<string | Buffer> | null")]
+    pub fn pick_files(&self) -> SyncResult<Option<Vec<Either<String, Buffer>>>> {
+        return SyncResult(show_many(FileMethod::PickFiles, &self.0));
     }
 
     /// Shows the file dialog and blocks the thread until the user closes it.
@@ -201,24 +340,9 @@ impl FileDialog {
     /// console.log(path);
     /// //=> '/home/username/Documents'
     /// ```
-    #[napi]
-    pub fn pick_folder(&mut self) -> Result<Option<String>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let path_buf = x.pick_folder();
-        if path_buf.is_none() {
-            return Ok(None);
-        }
-        let path_buf = path_buf.unwrap();
-        let path = path_buf.to_str();
-        if path.is_none() {
-            return Err(Error::from_reason("Invalid UTF-8"));
-        }
-        let path = path.unwrap();
-        let path = path.to_string();
-        return Ok(Some(path));
+    #[napi(ts_return_type = "string | Buffer | null")]
+    pub fn pick_folder(&self) -> SyncResult<Option<Either<String, Buffer>>> {
+        return SyncResult(show_one(FileMethod::PickFolder, &self.0));
     }
 
     /// Shows the file dialog and blocks the thread until the user closes it.
@@ -233,28 +357,9 @@ impl FileDialog {
     /// console.log(paths);
     /// //=> ['/home/username/Documents', ...]
     /// ```
-    #[napi]
-    pub fn pick_folders(&mut self) -> Result<Option<Vec<String>>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let path_bufs = x.pick_folders();
-        if path_bufs.is_none() {
-            return Ok(None);
-        }
-        let path_bufs = path_bufs.unwrap();
-        let mut paths = Vec::new();
-        for path_buf in path_bufs {
-            let path = path_buf.to_str();
-            if path.is_none() {
-                return Err(Error::from_reason("Invalid UTF-8"));
-            }
-            let path = path.unwrap();
-            let path = path.to_string();
-            paths.push(path);
-        }
-        return Ok(Some(paths));
+    #[napi(ts_return_type = "Array<string | Buffer> | null")]
+    pub fn pick_folders(&self) -> SyncResult<Option<Vec<Either<String, Buffer>>>> {
+        return SyncResult(show_many(FileMethod::PickFolders, &self.0));
     }
 
     /// Shows the file dialog and blocks the thread until the user closes it.
@@ -269,23 +374,63 @@ impl FileDialog {
     /// console.log(path);
     /// //=> '/home/username/Documents/hello.txt'
     /// ```
-    #[napi]
-    pub fn save_file(&mut self) -> Result<Option<String>> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let path_buf = x.save_file();
-        if path_buf.is_none() {
-            return Ok(None);
-        }
-        let path_buf = path_buf.unwrap();
-        let path = path_buf.to_str();
-        if path.is_none() {
-            return Err(Error::from_reason("Invalid UTF-8"));
-        }
-        let path = path.unwrap();
-        let path = path.to_string();
-        return Ok(Some(path));
+    #[napi(ts_return_type = "string | Buffer | null")]
+    pub fn save_file(&self) -> SyncResult<Option<Either<String, Buffer>>> {
+        return SyncResult(show_one(FileMethod::SaveFile, &self.0));
+    }
+
+    /// Shows a dialog for saving several files at once and blocks the thread
+    /// until the user closes it. The XDG desktop portal has a dedicated dialog
+    /// for this; everywhere else the user picks a folder. Returns the path of
+    /// each of `fileNames` in that folder, in the same order, or `null` if the
+    /// user canceled the dialog.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const paths = new FileDialog()
+    ///     .saveFiles(['cat.png', 'dog.png']);
+    /// console.log(paths);
+    /// //=> ['/home/username/Pictures/cat.png', '/home/username/Pictures/dog.png']
+    /// ```
+    #[napi(ts_return_type = "Array<string | Buffer> | null")]
+    pub fn save_files(
+        &self,
+        file_names: Vec<String>,
+    ) -> SyncResult<Option<Vec<Either<String, Buffer>>>> {
+        let mut x = self.0.clone();
+        x.file_names = file_names;
+        return SyncResult(show_many(FileMethod::SaveFiles, &x));
+    }
+}
+
+/// Shows a blocking dialog that picks one path and encodes it for JavaScript.
+fn show_one(method: FileMethod, x: &FileOptions) -> RfdResult<Option<Either<String, Buffer>>> {
+    let path_buf = backend::file_dialog(method, x)?;
+    if path_buf.is_none() {
+        return Ok(None);
+    }
+    let path_buf = path_buf.unwrap();
+    let encoding = x.path_encoding.unwrap_or(PathEncoding::Utf8);
+    let path = encoding.encode(path_buf)?;
+    return Ok(Some(path));
+}
+
+/// Like `show_one()` for the dialogs that pick many paths.
+fn show_many(
+    method: FileMethod,
+    x: &FileOptions,
+) -> RfdResult<Option<Vec<Either<String, Buffer>>>> {
+    let path_bufs = backend::file_dialogs(method, x)?;
+    if path_bufs.is_none() {
+        return Ok(None);
+    }
+    let path_bufs = path_bufs.unwrap();
+    let encoding = x.path_encoding.unwrap_or(PathEncoding::Utf8);
+    let mut paths = Vec::new();
+    for path_buf in path_bufs {
+        let path = encoding.encode(path_buf)?;
+        paths.push(path);
     }
+    return Ok(Some(paths));
 }
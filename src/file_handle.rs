@@ -1,9 +1,22 @@
+use super::error::*;
+use super::file_cursor::*;
+use super::file_stat::*;
+use super::file_stream::*;
+use super::file_watcher::*;
+use super::hash::*;
+use super::json::{self, Json};
+use super::line_stream::*;
+use super::mime;
+use super::path_encoding::*;
+use super::text::*;
 use napi::bindgen_prelude::*;
 use rfd;
-use std::path::PathBuf;
-use std::panic;
-use futures::future::FutureExt;
-
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// This is not recommended for use. The
 /// `FileHandle` class is only provided to achieve feature-parity with the
@@ -47,10 +60,13 @@ impl FileHandle {
     /// console.log(fileHandle.path());
     /// //=> '/home/username/Documents/hello.txt'
     /// ```
+    ///
+    /// The path can also be a `Buffer` with the raw bytes of a path that isn't
+    /// valid UTF-8, like the ones from `.setPathEncoding('Buffer')`.
     #[napi(factory)]
-    pub fn wrap(path_buf: String) -> Self {
-        let path_buf = PathBuf::from(path_buf);
-        return Self(rfd::FileHandle::wrap(path_buf));
+    pub fn wrap(path_buf: Either<String, Buffer>) -> Self {
+        let path_buf = path_from_js(path_buf);
+        return Self(rfd::FileHandle::from(path_buf));
     }
 
     /// Returns the file name of the file that this `FileHandle` refers to. This
@@ -92,16 +108,31 @@ impl FileHandle {
     /// console.log(path);
     /// //=> '/home/username/Documents/hello.txt'
     /// ```
-    #[napi]
-    pub fn path(&self) -> Result<String> {
+    #[napi(ts_return_type = "string")]
+    pub fn path(&self) -> SyncResult<String> {
         let path_buf = self.0.path();
         let path = path_buf.to_str();
         if path.is_none() {
-            return Err(Error::from_reason("Invalid UTF-8"));
+            return SyncResult(Err(RfdError::non_utf8_path(path_buf)));
         }
         let path = path.unwrap();
         let path = path.to_string();
-        return Ok(path);
+        return SyncResult(Ok(path));
+    }
+
+    /// Returns the raw bytes of the path that this `FileHandle` refers to.
+    /// Unlike `.path()`, this never throws an `ERR_NON_UTF8_PATH` error. The
+    /// result can be passed to any `node:fs` function.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// const bytes = await readFile(fileHandle.pathBuffer());
+    /// ```
+    #[napi]
+    pub fn path_buffer(&self) -> Buffer {
+        return path_to_buffer(self.0.path());
     }
 
     /// Reads the entire contents of the file into a vector of bytes. This is
@@ -130,26 +161,436 @@ impl FileHandle {
     /// //=> 'Hello, world!'
     /// ```
     ///
-    /// Note that if when `.read()` is called, the wrapped path that this
-    /// `FileHandle` refers to happens to be a folder, not a file, this method
-    /// will throw an error. This is different from `rfd` which currently
-    /// panics if the path is a folder.
+    /// If the file can't be read, the promise rejects with an `ERR_IO` error
+    /// that has the `kind`, `errno`, `syscall` and `path` of the failure, just
+    /// like `node:fs`. This includes the case where the path is a folder, which
+    /// `rfd` itself panics on.
     ///
     /// https://github.com/PolyMeilex/rfd/issues/125
+    ///
+    /// ```js
+    /// try {
+    ///   await fileHandle.read();
+    /// } catch (error) {
+    ///   console.log(error.kind, error.syscall);
+    ///   //=> 'PermissionDenied' 'open'
+    /// }
+    /// ```
+    #[napi(ts_return_type = "Promise<Buffer>")]
+    pub async unsafe fn read(&self) -> AsyncResult<Buffer> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || read_file(&path)).await;
+        return AsyncResult(result);
+    }
+
+    /// Reads `length` bytes starting at the byte `offset` instead of the whole
+    /// file. Resolves to a shorter `Buffer` if the file ends before that, and
+    /// to an empty one if `offset` is past the end.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// const header = await fileHandle.readRange(0, 512);
+    /// ```
+    #[napi(ts_return_type = "Promise<Buffer>")]
+    pub async unsafe fn read_range(&self, offset: i64, length: u32) -> AsyncResult<Buffer> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || read_range(&path, offset, length)).await;
+        return AsyncResult(result);
+    }
+
+    /// Opens the file for random access. The returned `FileCursor` keeps the
+    /// file open across `.read()` and `.seek()` calls so that they don't have
+    /// to reopen it every time like `.readRange()` does.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// const cursor = await fileHandle.open();
+    /// await cursor.seek(1024);
+    /// const bytes = await cursor.read(16);
+    /// cursor.close();
+    /// ```
+    #[napi(ts_return_type = "Promise<FileCursor>")]
+    pub async unsafe fn open(&self) -> AsyncResult<FileCursor> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || FileCursor::open(path)).await;
+        return AsyncResult(result);
+    }
+
+    /// Reads the size, timestamps, type, permissions and owner of the file
+    /// without blocking the main thread. Symbolic links are followed like
+    /// `fs.stat()` does. Rejects with an `ERR_IO` error if the file doesn't
+    /// exist.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// const { size, mtimeMs } = await fileHandle.stat();
+    /// console.log(size, new Date(mtimeMs));
+    /// //=> 13 2023-11-14T22:13:20.000Z
+    /// ```
+    #[napi(ts_return_type = "Promise<FileStat>")]
+    pub async unsafe fn stat(&self) -> AsyncResult<FileStat> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || stat(&path)).await;
+        return AsyncResult(result);
+    }
+
+    /// Guesses the MIME type of the file from its first few bytes, like the
+    /// `file` command does, and falls back to the file extension for formats
+    /// without a signature such as JSON or CSS. Unknown text files are
+    /// `'text/plain'`, unknown binary files `'application/octet-stream'` and
+    /// folders `'inode/directory'`. Runs off the main thread.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// if (!(await fileHandle.mimeType()).startsWith('image/')) {
+    ///   throw new Error('Please pick an image');
+    /// }
+    /// ```
+    #[napi(ts_return_type = "Promise<string>")]
+    pub async unsafe fn mime_type(&self) -> AsyncResult<String> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || mime::mime_type(&path)).await;
+        return AsyncResult(result);
+    }
+
+    /// Reads the file and decodes it as text. Without an `encoding`, a byte
+    /// order mark at the start picks UTF-8, UTF-16LE or UTF-16BE, and UTF-8 is
+    /// the default. The byte order mark is never part of the result. Invalid
+    /// sequences are replaced with U+FFFD like `TextDecoder` does. The
+    /// decoding happens on a worker thread.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// const text = await fileHandle.text({ encoding: 'latin1' });
+    /// ```
+    #[napi(ts_return_type = "Promise<string>")]
+    pub async unsafe fn text(&self, options: Option<TextOptions>) -> AsyncResult<String> {
+        let encoding = options.and_then(|x| x.encoding);
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || read_text(&path, encoding)).await;
+        return AsyncResult(result);
+    }
+
+    /// Reads the file as text like `.text()` does and parses it as JSON on a
+    /// worker thread. Instead of the `SyntaxError` from `JSON.parse()`, a
+    /// mistake in the file rejects with an `ERR_INVALID_DATA` error that has
+    /// the 1-based `line` and `column` of the mistake. Arrays and objects can
+    /// be nested up to 128 deep.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// try {
+    ///   const config = await fileHandle.json();
+    /// } catch (error) {
+    ///   console.log(error.code, error.line, error.column);
+    ///   //=> 'ERR_INVALID_DATA' 3 14
+    /// }
+    /// ```
+    #[napi(ts_return_type = "Promise<any>")]
+    pub async unsafe fn json(&self) -> AsyncResult<Json> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || read_json(&path)).await;
+        return AsyncResult(result);
+    }
+
+    /// Computes the digest of the file and resolves to it as lowercase hex.
+    /// The file is read and hashed a chunk at a time on a worker thread, so
+    /// it never has to fit in memory. The optional `onProgress` callback is
+    /// called after each MiB with how much has been hashed; the last call
+    /// happens before the promise resolves. If it throws, hashing stops and
+    /// the promise rejects with what it threw.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// const digest = await fileHandle.hash('sha256', ({ bytesHashed, totalBytes }) => {
+    ///   progressBar.value = bytesHashed / totalBytes;
+    /// });
+    /// console.log(digest);
+    /// //=> 'dffd6021bb2bd5b0af676290809ec3a53191dd81c7f70a4b28688a362182986f'
+    /// ```
+    #[napi(ts_return_type = "Promise<string>")]
+    pub async unsafe fn hash(
+        &self,
+        algorithm: HashAlgorithm,
+        #[napi(ts_arg_type = "(progress: HashProgress) => void")] on_progress: Option<
+            ProgressListener,
+        >,
+    ) -> HashResult {
+        return hash(self.0.path().to_path_buf(), algorithm, on_progress).await;
+    }
+
+    /// Reads the file in chunks instead of all at once like `.read()` does.
+    /// Returns a `FileStream` that can be used with `for await`. Use this for
+    /// large files that don't fit in memory. The optional `start` and `end`
+    /// byte offsets limit which part of the file is read.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// for await (const chunk of fileHandle.stream({ chunkSize: 1024 * 1024 })) {
+    ///   hash.update(chunk);
+    /// }
+    /// ```
     #[napi]
-    pub async unsafe fn read(&self) -> Result<Buffer> {
-        // TODO: https://stackoverflow.com/a/54432441
-        // https://stackoverflow.com/a/35559417
-        let hook = panic::take_hook();
-        panic::set_hook(Box::new(|_| {}));
-        // https://stackoverflow.com/a/63159560/19522682
-        let vec = self.0.read().catch_unwind().await;
-        panic::set_hook(hook);
-        if vec.is_err() {
-            return Err(Error::from_reason("Panic"));
+    pub fn stream(
+        &self,
+        env: Env,
+        options: Option<FileStreamOptions>,
+    ) -> Result<ClassInstance<FileStream>> {
+        let stream = match FileStream::new(self.0.path().to_path_buf(), options) {
+            Ok(stream) => stream,
+            Err(error) => return Err(error.into_napi(env)),
+        };
+        return stream.into_async_iterator(env);
+    }
+
+    /// Reads the file a line at a time, decoded like `.text()` does. Returns a
+    /// `LineStream` that can be used with `for await`. The `\n` or `\r\n` at
+    /// the end of each line is removed.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog().pickFile();
+    /// for await (const line of fileHandle.lines()) {
+    ///   console.log(line.split(','));
+    /// }
+    /// ```
+    #[napi]
+    pub fn lines(
+        &self,
+        env: Env,
+        options: Option<TextOptions>,
+    ) -> Result<ClassInstance<LineStream>> {
+        let stream = LineStream::new(self.0.path().to_path_buf(), options);
+        return stream.into_async_iterator(env);
+    }
+
+    /// Watches the file or folder for changes on disk. Returns a `FileWatcher`
+    /// that can be used with `for await` to get a `WatchEvent` for every file
+    /// that's created, modified, removed or renamed. Pass `recursive: true`
+    /// to include subfolders. Throws an `ERR_IO` error if the path doesn't
+    /// exist.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const folder = await new AsyncFileDialog().pickFolder();
+    /// for await (const event of folder.watch({ recursive: true })) {
+    ///   if (event.type === 'rename') {
+    ///     console.log(event.fileHandle.path(), '->', event.newFileHandle?.path());
+    ///   }
+    /// }
+    /// ```
+    #[napi]
+    pub fn watch(
+        &self,
+        env: Env,
+        options: Option<WatchOptions>,
+    ) -> Result<ClassInstance<FileWatcher>> {
+        let watcher = match FileWatcher::new(self.0.path().to_path_buf(), options) {
+            Ok(watcher) => watcher,
+            Err(error) => return Err(error.into_napi(env)),
+        };
+        return watcher.into_async_iterator(env);
+    }
+
+    /// Writes the bytes to the file, replacing its contents if it already
+    /// exists. This is the counterpart to `.read()` and is handy right after a
+    /// `.saveFile()` dialog since the file may not exist yet.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog()
+    ///     .setFileName('hello.txt')
+    ///     .saveFile();
+    /// await fileHandle.write(Buffer.from('Hello, world!'));
+    /// ```
+    #[napi(ts_return_type = "Promise<void>")]
+    pub async unsafe fn write(&self, data: Buffer) -> AsyncResult<()> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || write_file(&path, &data)).await;
+        return AsyncResult(result);
+    }
+
+    /// Appends the bytes to the end of the file, creating it if it doesn't
+    /// exist yet.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = FileHandle.wrap('/home/username/Documents/log.txt');
+    /// await fileHandle.append(Buffer.from('Another line\n'));
+    /// ```
+    #[napi(ts_return_type = "Promise<void>")]
+    pub async unsafe fn append(&self, data: Buffer) -> AsyncResult<()> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || append_file(&path, &data)).await;
+        return AsyncResult(result);
+    }
+
+    /// Same as `.write()` but never leaves a partially written file behind. The
+    /// bytes are first written to a temporary file next to the target which is
+    /// then renamed into place. If the process crashes midway, the original
+    /// file is left untouched. The new file keeps the permissions of the one
+    /// it replaces, and concurrent calls each use their own temporary file so
+    /// that the result is always one complete write.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const fileHandle = await new AsyncFileDialog()
+    ///     .setFileName('settings.json')
+    ///     .saveFile();
+    /// await fileHandle.writeAtomic(Buffer.from(JSON.stringify(settings)));
+    /// ```
+    #[napi(ts_return_type = "Promise<void>")]
+    pub async unsafe fn write_atomic(&self, data: Buffer) -> AsyncResult<()> {
+        let path = self.0.path().to_path_buf();
+        let result = spawn_io(move || write_atomic(&path, &data)).await;
+        return AsyncResult(result);
+    }
+}
+
+fn read_file(path: &Path) -> RfdResult<Buffer> {
+    let file = fs::File::open(path);
+    if file.is_err() {
+        return Err(RfdError::io(file.unwrap_err(), "open", path));
+    }
+    let mut file = file.unwrap();
+    let mut vec = Vec::new();
+    let result = file.read_to_end(&mut vec);
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "read", path));
+    }
+    return Ok(vec.into());
+}
+
+fn read_json(path: &Path) -> RfdResult<Json> {
+    let text = read_text(path, None)?;
+    return json::parse(&text, path);
+}
+
+fn write_file(path: &Path, data: &[u8]) -> RfdResult<()> {
+    let file = fs::File::create(path);
+    if file.is_err() {
+        return Err(RfdError::io(file.unwrap_err(), "open", path));
+    }
+    let mut file = file.unwrap();
+    let result = file.write_all(data);
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "write", path));
+    }
+    return Ok(());
+}
+
+fn append_file(path: &Path, data: &[u8]) -> RfdResult<()> {
+    let file = fs::OpenOptions::new().create(true).append(true).open(path);
+    if file.is_err() {
+        return Err(RfdError::io(file.unwrap_err(), "open", path));
+    }
+    let mut file = file.unwrap();
+    let result = file.write_all(data);
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "write", path));
+    }
+    return Ok(());
+}
+
+/// Counts the temporary files of `.writeAtomic()` so that concurrent calls in
+/// the same process never share one.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Creates a new temporary file next to `path`. `create_new` fails instead of
+/// following a file or symbolic link that's already there, in which case the
+/// next name is tried.
+fn create_tmp_file(path: &Path) -> RfdResult<(fs::File, PathBuf)> {
+    let file_name = path.file_name();
+    if file_name.is_none() {
+        return Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            format!("Not a file path: '{}'", path.to_string_lossy()),
+        ));
+    }
+    loop {
+        let mut tmp_name = OsString::from(".");
+        tmp_name.push(file_name.unwrap());
+        let count = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        tmp_name.push(format!(".{}.{}.tmp", process::id(), count));
+        let tmp_path = path.with_file_name(tmp_name);
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp_path);
+        if file.is_ok() {
+            return Ok((file.unwrap(), tmp_path));
+        }
+        let error = file.unwrap_err();
+        if error.kind() != io::ErrorKind::AlreadyExists {
+            return Err(RfdError::io(error, "open", &tmp_path));
+        }
+    }
+}
+
+/// Writes `data` to the temporary file, gives it the permissions of the file
+/// that it replaces, and renames it into place.
+fn replace_with(mut file: fs::File, tmp_path: &Path, path: &Path, data: &[u8]) -> RfdResult<()> {
+    let result = file.write_all(data);
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "write", tmp_path));
+    }
+    if let Ok(metadata) = fs::metadata(path) {
+        let result = file.set_permissions(metadata.permissions());
+        if result.is_err() {
+            return Err(RfdError::io(result.unwrap_err(), "chmod", tmp_path));
         }
-        let vec = vec.unwrap();
-        let buffer = vec.into();
-        return Ok(buffer);
     }
+    let result = file.sync_all();
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "fsync", tmp_path));
+    }
+    drop(file);
+    let result = fs::rename(tmp_path, path);
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "rename", path));
+    }
+    // The rename itself is only durable once the folder is synced too. Windows
+    // can't open folders as files, and NTFS journals renames anyway.
+    #[cfg(unix)]
+    {
+        let parent = match path.parent() {
+            Some(x) if !x.as_os_str().is_empty() => x,
+            _ => Path::new("."),
+        };
+        let result = fs::File::open(parent).and_then(|x| x.sync_all());
+        if result.is_err() {
+            return Err(RfdError::io(result.unwrap_err(), "fsync", parent));
+        }
+    }
+    return Ok(());
+}
+
+fn write_atomic(path: &Path, data: &[u8]) -> RfdResult<()> {
+    let (file, tmp_path) = create_tmp_file(path)?;
+    let result = replace_with(file, &tmp_path, path, data);
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    return result;
 }
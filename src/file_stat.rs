@@ -0,0 +1,144 @@
+use super::error::*;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What kind of file system entry a `FileHandle` refers to. Symbolic links are
+/// followed, so this is never a link.
+#[napi(string_enum)]
+pub enum FileType {
+    #[napi(value = "file")]
+    File,
+    #[napi(value = "directory")]
+    Directory,
+    #[napi(value = "blockDevice")]
+    BlockDevice,
+    #[napi(value = "characterDevice")]
+    CharacterDevice,
+    #[napi(value = "fifo")]
+    Fifo,
+    #[napi(value = "socket")]
+    Socket,
+    #[napi(value = "unknown")]
+    Unknown,
+}
+
+/// The metadata that `FileHandle.stat()` returns. The timestamps are in
+/// milliseconds since the Unix epoch like the `*Ms` properties of the
+/// `fs.Stats` from `node:fs`, so `new Date(stat.mtimeMs)` works.
+///
+/// Example:
+///
+/// ```js
+/// const stat = await fileHandle.stat();
+/// console.log(stat);
+/// //=> { size: 13, type: 'file', mode: 33188, uid: 1000, gid: 1000,
+/// //     atimeMs: 1700000000000, mtimeMs: 1700000000000,
+/// //     ctimeMs: 1700000000000, birthtimeMs: 1690000000000 }
+/// ```
+#[napi(object)]
+pub struct FileStat {
+    /// The size in bytes.
+    pub size: f64,
+    #[napi(js_name = "type")]
+    pub file_type: FileType,
+    /// The permission and file type bits like `st_mode`. On Windows, only the
+    /// read-only attribute is reflected as `0o444` instead of `0o666`.
+    pub mode: u32,
+    /// The owning user. `null` on Windows.
+    pub uid: Option<u32>,
+    /// The owning group. `null` on Windows.
+    pub gid: Option<u32>,
+    pub atime_ms: f64,
+    pub mtime_ms: f64,
+    /// When the metadata last changed. Windows has no such timestamp, so it's
+    /// the same as `mtimeMs` there.
+    pub ctime_ms: f64,
+    /// When the file was created, or `null` if the file system doesn't record
+    /// it.
+    pub birthtime_ms: Option<f64>,
+}
+
+fn millis(time: SystemTime) -> f64 {
+    return match time.duration_since(UNIX_EPOCH) {
+        Ok(x) => x.as_secs_f64() * 1000.0,
+        Err(x) => -x.duration().as_secs_f64() * 1000.0,
+    };
+}
+
+#[cfg(unix)]
+fn file_type(metadata: &fs::Metadata) -> FileType {
+    use std::os::unix::fs::FileTypeExt;
+    let x = metadata.file_type();
+    if x.is_file() {
+        return FileType::File;
+    }
+    if x.is_dir() {
+        return FileType::Directory;
+    }
+    if x.is_block_device() {
+        return FileType::BlockDevice;
+    }
+    if x.is_char_device() {
+        return FileType::CharacterDevice;
+    }
+    if x.is_fifo() {
+        return FileType::Fifo;
+    }
+    if x.is_socket() {
+        return FileType::Socket;
+    }
+    return FileType::Unknown;
+}
+
+#[cfg(not(unix))]
+fn file_type(metadata: &fs::Metadata) -> FileType {
+    if metadata.is_file() {
+        return FileType::File;
+    }
+    if metadata.is_dir() {
+        return FileType::Directory;
+    }
+    return FileType::Unknown;
+}
+
+/// Reads the metadata of `path` like `fs.stat()`, following symbolic links.
+pub(crate) fn stat(path: &Path) -> RfdResult<FileStat> {
+    let metadata = fs::metadata(path);
+    if metadata.is_err() {
+        return Err(RfdError::io(metadata.unwrap_err(), "stat", path));
+    }
+    let metadata = metadata.unwrap();
+    let mtime_ms = metadata.modified().map(millis).unwrap_or(0.0);
+    #[cfg(unix)]
+    let (mode, uid, gid, ctime_ms) = {
+        use std::os::unix::fs::MetadataExt;
+        let ctime_ms = metadata.ctime() as f64 * 1000.0 + metadata.ctime_nsec() as f64 / 1e6;
+        (
+            metadata.mode(),
+            Some(metadata.uid()),
+            Some(metadata.gid()),
+            ctime_ms,
+        )
+    };
+    #[cfg(not(unix))]
+    let (mode, uid, gid, ctime_ms) = {
+        let permissions = if metadata.permissions().readonly() {
+            0o444
+        } else {
+            0o666
+        };
+        (permissions, None, None, mtime_ms)
+    };
+    return Ok(FileStat {
+        size: metadata.len() as f64,
+        file_type: file_type(&metadata),
+        mode,
+        uid,
+        gid,
+        atime_ms: metadata.accessed().map(millis).unwrap_or(mtime_ms),
+        mtime_ms,
+        ctime_ms,
+        birthtime_ms: metadata.created().ok().map(millis),
+    });
+}
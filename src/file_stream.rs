@@ -0,0 +1,230 @@
+use super::closable::Closable;
+use super::error::*;
+use napi::bindgen_prelude::*;
+use napi::{CallContext, JsObject, JsUnknown};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Options for `FileHandle.stream()`. `start` and `end` are byte offsets into
+/// the file. Like `Blob.slice()`, `end` is exclusive and defaults to the end of
+/// the file. The `chunkSize` defaults to 64 KiB.
+#[napi(object)]
+pub struct FileStreamOptions {
+    pub chunk_size: Option<u32>,
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+}
+
+/// The `{ done, value }` object produced by `FileStream.next()`. This is the
+/// same shape as any other JavaScript iterator result.
+#[napi(object)]
+pub struct FileStreamResult {
+    pub done: bool,
+    pub value: Option<Buffer>,
+}
+
+struct FileStreamState {
+    file: Option<File>,
+    position: u64,
+    end: Option<u64>,
+    done: bool,
+}
+
+/// An async iterator over the chunks of a file. Get one from
+/// `FileHandle.stream()` and use it with `for await` so that only one chunk is
+/// in memory at a time. Each chunk is read on a worker thread. Breaking out of
+/// the loop or calling `.cancel()` closes the underlying file.
+///
+/// Example:
+///
+/// ```js
+/// const fileHandle = await new AsyncFileDialog().pickFile();
+/// let size = 0;
+/// for await (const chunk of fileHandle.stream({ chunkSize: 1024 * 1024 })) {
+///   size += chunk.length;
+/// }
+/// console.log(size);
+/// //=> 4294967296
+/// ```
+///
+/// If you need a web `ReadableStream`, you can wrap it:
+///
+/// ```js
+/// const readable = ReadableStream.from(fileHandle.stream());
+/// ```
+#[napi]
+pub struct FileStream {
+    path: PathBuf,
+    start: u64,
+    chunk_size: usize,
+    state: Arc<Closable<FileStreamState>>,
+}
+#[napi]
+impl FileStream {
+    pub(crate) fn new(path: PathBuf, options: Option<FileStreamOptions>) -> RfdResult<Self> {
+        let (chunk_size, start, end) = match options {
+            Some(x) => (x.chunk_size, x.start, x.end),
+            None => (None, None, None),
+        };
+        let chunk_size = chunk_size.unwrap_or(64 * 1024);
+        if chunk_size == 0 {
+            return Err(RfdError::new(
+                ErrorCode::InvalidArg,
+                "chunkSize must be greater than 0",
+            ));
+        }
+        let start = start.unwrap_or(0);
+        if start < 0 || end.unwrap_or(0) < 0 {
+            return Err(RfdError::new(
+                ErrorCode::InvalidArg,
+                "start and end must not be negative",
+            ));
+        }
+        return Ok(Self {
+            path,
+            start: start as u64,
+            chunk_size: chunk_size as usize,
+            state: Arc::new(Closable::new(FileStreamState {
+                file: None,
+                position: start as u64,
+                end: end.map(|x| x as u64),
+                done: false,
+            })),
+        });
+    }
+
+    /// Creates the JavaScript object for this stream and makes it usable with
+    /// `for await` by adding a `[Symbol.asyncIterator]()` method.
+    pub(crate) fn into_async_iterator(self, env: Env) -> Result<ClassInstance<Self>> {
+        let instance = self.into_instance(env)?;
+        make_async_iterable(env, instance.as_object(env))?;
+        return Ok(instance);
+    }
+
+    /// Reads the next chunk of the file. Resolves to `{ done: true }` once the
+    /// end (or the `end` option) has been reached or the stream was cancelled.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const stream = fileHandle.stream();
+    /// const { done, value } = await stream.next();
+    /// ```
+    #[napi(ts_return_type = "Promise<FileStreamResult>")]
+    pub async unsafe fn next(&self) -> AsyncResult<FileStreamResult> {
+        let state = self.state.clone();
+        let path = self.path.clone();
+        let (start, chunk_size) = (self.start, self.chunk_size);
+        let chunk = spawn_io(move || {
+            let chunk = state.with(|state| read_chunk(&path, start, chunk_size, state));
+            return chunk.unwrap_or(Ok(None));
+        })
+        .await;
+        return match chunk {
+            Ok(None) => AsyncResult(Ok(FileStreamResult {
+                done: true,
+                value: None,
+            })),
+            Ok(Some(chunk)) => AsyncResult(Ok(FileStreamResult {
+                done: false,
+                value: Some(chunk),
+            })),
+            Err(error) => AsyncResult(Err(error)),
+        };
+    }
+
+    /// Stops reading and closes the file. A pending `.next()` and any further
+    /// ones resolve to `{ done: true }`. It returns right away even while a
+    /// read is in progress; the file is closed once that read is done. This is
+    /// called for you when you `break` out of a `for await` loop.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const stream = fileHandle.stream();
+    /// await stream.next();
+    /// stream.cancel();
+    /// ```
+    #[napi]
+    pub fn cancel(&self) {
+        self.state.close();
+    }
+
+    /// The async iterator protocol's `return()` method. Same as `.cancel()`.
+    #[napi(js_name = "return")]
+    pub async unsafe fn return_(&self) -> FileStreamResult {
+        self.cancel();
+        return FileStreamResult {
+            done: true,
+            value: None,
+        };
+    }
+}
+
+/// Adds a `[Symbol.asyncIterator]()` method that returns the object itself,
+/// so that an object with a `next()` method works with `for await`.
+pub(crate) fn make_async_iterable(env: Env, mut object: JsObject) -> Result<()> {
+    let symbol: JsObject = env.get_global()?.get_named_property_unchecked("Symbol")?;
+    let async_iterator: JsUnknown = symbol.get_named_property("asyncIterator")?;
+    let function = env.create_function("asyncIterator", return_this)?;
+    object.set_property(async_iterator, function)?;
+    return Ok(());
+}
+
+/// Reads the next chunk of the file at `path` on a worker thread, or `None` at
+/// the end. The file is opened and moved to `start` on the first call.
+fn read_chunk(
+    path: &Path,
+    start: u64,
+    chunk_size: usize,
+    state: &mut FileStreamState,
+) -> RfdResult<Option<Buffer>> {
+    if state.done {
+        return Ok(None);
+    }
+    if state.file.is_none() {
+        let file = File::open(path);
+        if file.is_err() {
+            state.done = true;
+            return Err(RfdError::io(file.unwrap_err(), "open", path));
+        }
+        let mut file = file.unwrap();
+        let result = file.seek(SeekFrom::Start(start));
+        if result.is_err() {
+            state.done = true;
+            return Err(RfdError::io(result.unwrap_err(), "seek", path));
+        }
+        state.file = Some(file);
+    }
+
+    let mut length = chunk_size as u64;
+    if let Some(end) = state.end {
+        length = length.min(end.saturating_sub(state.position));
+    }
+    let mut vec = Vec::with_capacity(length as usize);
+    let result = state
+        .file
+        .as_mut()
+        .unwrap()
+        .take(length)
+        .read_to_end(&mut vec);
+    if result.is_err() {
+        state.done = true;
+        state.file = None;
+        return Err(RfdError::io(result.unwrap_err(), "read", path));
+    }
+    if vec.is_empty() {
+        state.done = true;
+        state.file = None;
+        return Ok(None);
+    }
+    state.position += vec.len() as u64;
+    return Ok(Some(vec.into()));
+}
+
+#[js_function]
+fn return_this(ctx: CallContext) -> Result<JsObject> {
+    return ctx.this();
+}
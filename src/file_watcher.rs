@@ -0,0 +1,278 @@
+use super::error::*;
+use super::file_handle::*;
+use super::file_stream::make_async_iterable;
+use napi::bindgen_prelude::*;
+use napi::tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use napi::tokio::sync::Mutex as AsyncMutex;
+use notify::event::{EventKind, ModifyKind, RenameMode};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use rfd;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for the new name after the old name of a renamed file.
+/// Without one, the file was moved out of the watched folder.
+const RENAME_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// What happened to a file or folder in a `WatchEvent`.
+#[napi(string_enum)]
+pub enum WatchEventType {
+    #[napi(value = "create")]
+    Create,
+    #[napi(value = "modify")]
+    Modify,
+    #[napi(value = "remove")]
+    Remove,
+    #[napi(value = "rename")]
+    Rename,
+}
+
+/// Options for `FileHandle.watch()`. With `recursive`, changes in subfolders
+/// of a folder are reported too, including ones created later.
+#[napi(object)]
+pub struct WatchOptions {
+    pub recursive: Option<bool>,
+}
+
+/// A change to a file or folder reported by a `FileWatcher`.
+#[napi(object, object_from_js = false)]
+pub struct WatchEvent {
+    #[napi(js_name = "type")]
+    pub event_type: WatchEventType,
+    /// The file or folder that changed. For a `rename`, this is the old path.
+    pub file_handle: FileHandle,
+    /// The new path for a `rename`. It's left out for other events, and on
+    /// macOS, which reports the old and new path as separate `rename` events.
+    pub new_file_handle: Option<FileHandle>,
+}
+
+/// The `{ done, value }` object produced by `FileWatcher.next()`.
+#[napi(object, object_from_js = false)]
+pub struct FileWatcherResult {
+    pub done: bool,
+    pub value: Option<WatchEvent>,
+}
+
+/// A `WatchEvent` before it's turned into JavaScript objects.
+struct Change(WatchEventType, PathBuf, Option<PathBuf>);
+
+fn watch_error(error: notify::Error, path: &Path) -> RfdError {
+    let path = error.paths.first().map_or(path, |x| x.as_path());
+    let error = match error.kind {
+        notify::ErrorKind::Io(x) => x,
+        notify::ErrorKind::PathNotFound => io::Error::from(io::ErrorKind::NotFound),
+        kind => io::Error::other(format!("{:?}", kind)),
+    };
+    return RfdError::io(error, "watch", path);
+}
+
+/// Turns the events of `notify` into `Change`s until the watcher is dropped.
+/// Platforms report the old and new name of a renamed file separately, so
+/// those are paired up here into a single `rename`.
+fn translate(
+    events: Receiver<notify::Result<Event>>,
+    changes: UnboundedSender<RfdResult<Change>>,
+    path: PathBuf,
+) {
+    // The old name of a renamed file, waiting for its new name.
+    let mut from: Option<Event> = None;
+    loop {
+        let event = if from.is_some() {
+            events.recv_timeout(RENAME_TIMEOUT)
+        } else {
+            events.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        };
+        let event = match event {
+            Ok(Ok(event)) => event,
+            Ok(Err(error)) => {
+                let _ = changes.send(Err(watch_error(error, &path)));
+                continue;
+            }
+            Err(error) => {
+                // Nothing took the new name, so the file was moved away.
+                if let Some(from) = from.take() {
+                    for path in from.paths {
+                        let _ = changes.send(Ok(Change(WatchEventType::Remove, path, None)));
+                    }
+                }
+                if error == RecvTimeoutError::Disconnected {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        if let EventKind::Modify(ModifyKind::Name(RenameMode::To)) = event.kind {
+            if from
+                .as_ref()
+                .is_some_and(|x| x.tracker() == event.tracker())
+            {
+                let old = from.take().unwrap().paths.into_iter().next();
+                let new = event.paths.into_iter().next();
+                if let (Some(old), Some(new)) = (old, new) {
+                    let _ = changes.send(Ok(Change(WatchEventType::Rename, old, Some(new))));
+                }
+                continue;
+            }
+        }
+        if let Some(from) = from.take() {
+            for path in from.paths {
+                let _ = changes.send(Ok(Change(WatchEventType::Remove, path, None)));
+            }
+        }
+        let event_type = match event.kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                from = Some(event);
+                continue;
+            }
+            // Moved in from outside of the watched folder.
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => WatchEventType::Create,
+            // Already paired up from the `From` and `To` events.
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => continue,
+            EventKind::Modify(ModifyKind::Name(_)) => WatchEventType::Rename,
+            EventKind::Create(_) => WatchEventType::Create,
+            EventKind::Modify(_) => WatchEventType::Modify,
+            EventKind::Remove(_) => WatchEventType::Remove,
+            _ => continue,
+        };
+        for path in event.paths {
+            let _ = changes.send(Ok(Change(event_type, path, None)));
+        }
+        if changes.is_closed() {
+            return;
+        }
+    }
+}
+
+/// An async iterator over the changes to a file or folder on disk. Get one
+/// from `FileHandle.watch()` and use it with `for await`. It uses inotify on
+/// Linux, FSEvents on macOS and `ReadDirectoryChangesW` on Windows. Changes
+/// are queued until `.next()` is called, so none are missed between calls.
+/// Breaking out of the loop or calling `.close()` stops watching.
+///
+/// Example:
+///
+/// ```js
+/// const folder = await new AsyncFileDialog().pickFolder();
+/// for await (const event of folder.watch({ recursive: true })) {
+///   console.log(event.type, event.fileHandle.path());
+///   //=> 'create' '/home/username/project/notes.txt'
+/// }
+/// ```
+#[napi]
+pub struct FileWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    changes: AsyncMutex<UnboundedReceiver<RfdResult<Change>>>,
+    closed: AtomicBool,
+}
+#[napi]
+impl FileWatcher {
+    /// Starts watching `path`. Fails if it doesn't exist.
+    pub(crate) fn new(path: PathBuf, options: Option<WatchOptions>) -> RfdResult<Self> {
+        let recursive = options.and_then(|x| x.recursive).unwrap_or(false);
+        let (sender, receiver) = channel();
+        let watcher = notify::recommended_watcher(sender);
+        if watcher.is_err() {
+            return Err(watch_error(watcher.unwrap_err(), &path));
+        }
+        let mut watcher = watcher.unwrap();
+        let mode = match recursive {
+            true => RecursiveMode::Recursive,
+            false => RecursiveMode::NonRecursive,
+        };
+        let result = watcher.watch(&path, mode);
+        if result.is_err() {
+            return Err(watch_error(result.unwrap_err(), &path));
+        }
+        let (changes, changes_receiver) = unbounded_channel();
+        thread::spawn(move || translate(receiver, changes, path));
+        return Ok(Self {
+            watcher: Mutex::new(Some(watcher)),
+            changes: AsyncMutex::new(changes_receiver),
+            closed: AtomicBool::new(false),
+        });
+    }
+
+    /// Creates the JavaScript object for this watcher and makes it usable with
+    /// `for await`.
+    pub(crate) fn into_async_iterator(self, env: Env) -> Result<ClassInstance<Self>> {
+        let instance = self.into_instance(env)?;
+        make_async_iterable(env, instance.as_object(env))?;
+        return Ok(instance);
+    }
+
+    /// Waits for the next change. Resolves to `{ done: true }` once the
+    /// watcher was closed. Rejects with an `ERR_IO` error if watching fails,
+    /// for example because the inotify watch limit was reached.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const watcher = fileHandle.watch();
+    /// const { value } = await watcher.next();
+    /// console.log(value.type);
+    /// //=> 'modify'
+    /// ```
+    #[napi(ts_return_type = "Promise<FileWatcherResult>")]
+    pub async unsafe fn next(&self) -> AsyncResult<FileWatcherResult> {
+        let mut changes = self.changes.lock().await;
+        let change = match self.closed.load(Ordering::SeqCst) {
+            true => None,
+            false => changes.recv().await,
+        };
+        if change.is_none() || self.closed.load(Ordering::SeqCst) {
+            return AsyncResult(Ok(FileWatcherResult {
+                done: true,
+                value: None,
+            }));
+        }
+        let change = match change.unwrap() {
+            Ok(change) => change,
+            Err(error) => return AsyncResult(Err(error)),
+        };
+        let Change(event_type, path, new_path) = change;
+        return AsyncResult(Ok(FileWatcherResult {
+            done: false,
+            value: Some(WatchEvent {
+                event_type,
+                file_handle: FileHandle(rfd::FileHandle::from(path)),
+                new_file_handle: new_path.map(|x| FileHandle(rfd::FileHandle::from(x))),
+            }),
+        }));
+    }
+
+    /// Stops watching. A pending `.next()` and any further ones resolve to
+    /// `{ done: true }`. This is called for you when you `break` out of a
+    /// `for await` loop. Calling it again does nothing.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const watcher = folder.watch();
+    /// setTimeout(() => watcher.close(), 60_000);
+    /// for await (const event of watcher) {
+    ///   console.log(event);
+    /// }
+    /// ```
+    #[napi]
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        let mut watcher = self.watcher.lock().unwrap();
+        *watcher = None;
+    }
+
+    /// The async iterator protocol's `return()` method. Same as `.close()`.
+    #[napi(js_name = "return")]
+    pub async unsafe fn return_(&self) -> FileWatcherResult {
+        self.close();
+        return FileWatcherResult {
+            done: true,
+            value: None,
+        };
+    }
+}
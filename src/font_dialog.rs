@@ -0,0 +1,235 @@
+use super::backend;
+use super::error::*;
+use super::parent_window::*;
+use napi::bindgen_prelude::*;
+
+/// The slant of a font, with the same names as the CSS `font-style` property.
+#[napi(string_enum)]
+pub enum FontStyle {
+    #[napi(value = "normal")]
+    Normal,
+    #[napi(value = "italic")]
+    Italic,
+    #[napi(value = "oblique")]
+    Oblique,
+}
+
+/// The font that `FontDialog.show()` and `AsyncFontDialog.show()` return. The
+/// fields match the CSS `font-family`, `font-style`, `font-weight` and
+/// `font-size` properties.
+///
+/// Example:
+///
+/// ```js
+/// const font = new FontDialog().setFamily('Fira Code').show();
+/// console.log(font);
+/// //=> { family: 'Fira Code', style: 'normal', weight: 400, size: 12 }
+/// ```
+#[napi(object)]
+#[derive(Clone)]
+pub struct Font {
+    pub family: String,
+    pub style: FontStyle,
+    /// From 100 (thin) to 900 (black). 400 is regular and 700 is bold.
+    pub weight: u32,
+    /// The size in points.
+    pub size: f64,
+}
+
+/// The plain-data configuration of a font dialog. Shared by the `FontDialog`
+/// and `AsyncFontDialog` builders.
+#[derive(Clone, Default)]
+pub(crate) struct FontOptions {
+    pub(crate) title: Option<String>,
+    pub(crate) family: Option<String>,
+    pub(crate) style: Option<FontStyle>,
+    pub(crate) weight: Option<u32>,
+    pub(crate) size: Option<f64>,
+    pub(crate) parent: Option<ParentWindow>,
+}
+
+/// The builder for a synchronous font chooser. It's recommended to use the
+/// async `AsyncFontDialog` builder instead since it lets the Node.js event
+/// loop continue even while the dialog is open.
+///
+/// Neither `rfd` nor `zenity` and `kdialog` have a font chooser, so for now
+/// only the `mock` backend can show this dialog. Everything else throws
+/// `ERR_BACKEND_UNAVAILABLE`; check `capabilities().fontDialog.available`
+/// first.
+///
+/// Example:
+///
+/// ```js
+/// const font = new FontDialog()
+///     .setTitle('Editor font')
+///     .setFamily('Fira Code')
+///     .setSize(12)
+///     .show();
+/// console.log(font?.family);
+/// //=> 'JetBrains Mono'
+/// ```
+#[napi]
+#[repr(transparent)]
+pub struct FontDialog(pub(super) FontOptions);
+impl Default for FontDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[napi]
+impl FontDialog {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        return Self(FontOptions::default());
+    }
+
+    /// Returns a copy of this font dialog with the same settings.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const pick = new FontDialog().setSize(12);
+    /// const font = pick.clone().setFamily('Fira Code').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
+    }
+
+    /// Sets the title of the font dialog. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = new FontDialog().setTitle('Editor font').show();
+    /// ```
+    #[napi]
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Sets the font family that is selected when the dialog opens. Returns
+    /// `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = new FontDialog().setFamily('Fira Code').show();
+    /// ```
+    #[napi]
+    pub fn set_family(&mut self, this: This, family: String) -> This {
+        let x = &mut self.0;
+        x.family = Some(family);
+        return this;
+    }
+
+    /// Sets the style that is selected when the dialog opens. Returns `this`
+    /// for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = new FontDialog().setStyle('italic').show();
+    /// ```
+    #[napi]
+    pub fn set_style(&mut self, this: This, style: FontStyle) -> This {
+        let x = &mut self.0;
+        x.style = Some(style);
+        return this;
+    }
+
+    /// Sets the weight that is selected when the dialog opens, from 100 to
+    /// 900. Throws an `ERR_INVALID_ARG` error for anything else. Returns `this`
+    /// for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = new FontDialog().setWeight(700).show();
+    /// ```
+    #[napi]
+    pub fn set_weight(&mut self, this: This, weight: u32) -> Result<This, ErrorCode> {
+        check_weight(weight)?;
+        let x = &mut self.0;
+        x.weight = Some(weight);
+        return Ok(this);
+    }
+
+    /// Sets the size in points that is selected when the dialog opens. Throws
+    /// an `ERR_INVALID_ARG` error unless it's positive. Returns `this` for
+    /// chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = new FontDialog().setSize(10.5).show();
+    /// ```
+    #[napi]
+    pub fn set_size(&mut self, this: This, size: f64) -> Result<This, ErrorCode> {
+        check_size(size)?;
+        let x = &mut self.0;
+        x.size = Some(size);
+        return Ok(this);
+    }
+
+    /// Makes the font dialog modal for a parent window. See
+    /// `MessageDialog.setParent()` for the accepted handles.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = new FontDialog().setParent(handle).show();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
+    }
+
+    /// Shows the font dialog. Returns the picked font, or `null` if the dialog
+    /// was cancelled. Like the other builders, it can be shown again
+    /// afterwards.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const font = new FontDialog().show();
+    /// if (font) {
+    ///   editor.style.font = `${font.style} ${font.weight} ${font.size}pt ${font.family}`;
+    /// }
+    /// ```
+    #[napi(ts_return_type = "Font | null")]
+    pub fn show(&self) -> SyncResult<Option<Font>> {
+        let x = &self.0;
+        let y = backend::font_dialog(x);
+        return SyncResult(y);
+    }
+}
+
+pub(crate) fn check_weight(weight: u32) -> RfdResult<()> {
+    if !(100..=900).contains(&weight) {
+        return Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            format!("Font weights go from 100 to 900, got {}", weight),
+        ));
+    }
+    return Ok(());
+}
+
+pub(crate) fn check_size(size: f64) -> RfdResult<()> {
+    if !(size > 0.0 && size.is_finite()) {
+        return Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            format!("Font sizes have to be positive, got {}", size),
+        ));
+    }
+    return Ok(());
+}
@@ -0,0 +1,263 @@
+use super::error::*;
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{
+    ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+use napi::{JsFunction, JsUnknown, Ref};
+use sha2::Digest;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+
+/// How much of the file is read and hashed at a time. Progress is reported
+/// after each chunk.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// The digests that `FileHandle.hash()` can compute.
+#[napi(string_enum)]
+pub enum HashAlgorithm {
+    #[napi(value = "sha256")]
+    Sha256,
+    #[napi(value = "sha1")]
+    Sha1,
+    #[napi(value = "md5")]
+    Md5,
+    #[napi(value = "blake3")]
+    Blake3,
+}
+
+/// What the progress callback of `FileHandle.hash()` is called with.
+#[napi(object)]
+pub struct HashProgress {
+    pub bytes_hashed: f64,
+    /// The size of the file when hashing started.
+    pub total_bytes: f64,
+}
+
+struct ProgressState {
+    callback: Option<Ref<()>>,
+    /// What the callback threw. Once it's set, the callback isn't called again
+    /// and `FileHandle.hash()` rejects with it.
+    exception: Option<Error>,
+}
+
+/// The progress callback of `FileHandle.hash()`, called from the worker thread.
+/// The callback is wrapped in a function that catches what it throws, since an
+/// exception thrown through a threadsafe function would crash the process.
+#[derive(Clone)]
+pub struct ProgressListener {
+    function: ThreadsafeFunction<HashProgress, ErrorStrategy::Fatal>,
+    state: Arc<Mutex<ProgressState>>,
+}
+impl ProgressListener {
+    fn failed(&self) -> bool {
+        return self.state.lock().unwrap().exception.is_some();
+    }
+
+    /// Releases the callback and returns what it threw, if anything.
+    fn remove(self, env: Env) -> Result<Option<Error>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(mut callback) = state.callback.take() {
+            callback.unref(env)?;
+        }
+        return Ok(state.exception.take());
+    }
+}
+impl TypeName for ProgressListener {
+    fn type_name() -> &'static str {
+        return "Function";
+    }
+
+    fn value_type() -> ValueType {
+        return ValueType::Function;
+    }
+}
+impl ValidateNapiValue for ProgressListener {}
+impl FromNapiValue for ProgressListener {
+    unsafe fn from_napi_value(env: sys::napi_env, napi_val: sys::napi_value) -> Result<Self> {
+        let env = Env::from_raw(env);
+        let callback = JsFunction::from_napi_value(env.raw(), napi_val)?;
+        let state = Arc::new(Mutex::new(ProgressState {
+            callback: Some(env.create_reference(callback)?),
+            exception: None,
+        }));
+        let wrapper_state = state.clone();
+        let wrapper = env.create_function_from_closure("onProgress", move |ctx| {
+            let callback: JsFunction = {
+                let state = wrapper_state.lock().unwrap();
+                if state.exception.is_some() || state.callback.is_none() {
+                    return ctx.env.get_undefined();
+                }
+                ctx.env
+                    .get_reference_value(state.callback.as_ref().unwrap())?
+            };
+            let progress: JsUnknown = ctx.get(0)?;
+            if let Err(error) = callback.call(None, &[progress]) {
+                wrapper_state.lock().unwrap().exception = Some(error);
+            }
+            return ctx.env.get_undefined();
+        })?;
+        let function =
+            wrapper.create_threadsafe_function(0, |ctx: ThreadSafeCallContext<HashProgress>| {
+                return Ok(vec![ctx.value]);
+            })?;
+        return Ok(Self { function, state });
+    }
+}
+
+/// What `FileHandle.hash()` returns. This is an `AsyncResult` that rejects
+/// with what the progress callback threw instead, and releases the callback
+/// once the promise settles, which has to happen on the main thread.
+pub struct HashResult(RfdResult<String>, Option<ProgressListener>);
+impl ToNapiValue for HashResult {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+        if let Some(listener) = val.1 {
+            if let Some(exception) = listener.remove(Env::from_raw(env))? {
+                return Err(exception);
+            }
+        }
+        return AsyncResult::to_napi_value(env, AsyncResult(val.0));
+    }
+}
+
+enum Hasher {
+    Sha256(sha2::Sha256),
+    Sha1(sha1::Sha1),
+    Md5(md5::Md5),
+    Blake3(Box<blake3::Hasher>),
+}
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        return match algorithm {
+            HashAlgorithm::Sha256 => Self::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Sha1 => Self::Sha1(sha1::Sha1::new()),
+            HashAlgorithm::Md5 => Self::Md5(md5::Md5::new()),
+            HashAlgorithm::Blake3 => Self::Blake3(Box::new(blake3::Hasher::new())),
+        };
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Sha256(x) => x.update(bytes),
+            Self::Sha1(x) => x.update(bytes),
+            Self::Md5(x) => x.update(bytes),
+            Self::Blake3(x) => {
+                x.update(bytes);
+            }
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        return match self {
+            Self::Sha256(x) => x.finalize().to_vec(),
+            Self::Sha1(x) => x.finalize().to_vec(),
+            Self::Md5(x) => x.finalize().to_vec(),
+            Self::Blake3(x) => x.finalize().as_bytes().to_vec(),
+        };
+    }
+}
+
+/// Calls the progress callback and waits until it has run, so that the last
+/// call happens before the promise of `FileHandle.hash()` resolves.
+fn report_and_wait(on_progress: &ProgressListener, progress: HashProgress) {
+    let (sender, receiver) = mpsc::channel();
+    let status = on_progress.function.call_with_return_value(
+        progress,
+        ThreadsafeFunctionCallMode::Blocking,
+        move |_: JsUnknown| {
+            let _ = sender.send(());
+            return Ok(());
+        },
+    );
+    if status == Status::Ok {
+        // The wrapper never throws, so this only fails if the environment is
+        // shutting down and the call was dropped.
+        let _ = receiver.recv();
+    }
+}
+
+/// Reads the file at `path` in chunks on a blocking worker thread and resolves
+/// to its digest as lowercase hex.
+pub(crate) async fn hash(
+    path: PathBuf,
+    algorithm: HashAlgorithm,
+    on_progress: Option<ProgressListener>,
+) -> HashResult {
+    let listener = on_progress.clone();
+    let result = spawn_io(move || hash_file(&path, algorithm, listener)).await;
+    return HashResult(result, on_progress);
+}
+
+/// Hashes the file at `path`. Stops early if the progress callback throws.
+fn hash_file(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    on_progress: Option<ProgressListener>,
+) -> RfdResult<String> {
+    let file = File::open(path);
+    if file.is_err() {
+        return Err(RfdError::io(file.unwrap_err(), "open", path));
+    }
+    let mut file = file.unwrap();
+    let metadata = file.metadata();
+    if metadata.is_err() {
+        return Err(RfdError::io(metadata.unwrap_err(), "fstat", path));
+    }
+    let total_bytes = metadata.unwrap().len() as f64;
+
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = vec![0; CHUNK_SIZE];
+    let mut bytes_hashed = 0.0;
+    loop {
+        let length = file.read(&mut buffer);
+        if length.is_err() {
+            let error = length.unwrap_err();
+            if error.kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(RfdError::io(error, "read", path));
+        }
+        let length = length.unwrap();
+        if length == 0 {
+            break;
+        }
+        // Each chunk is reported once the next one was read, so that the last
+        // one is reported only once below.
+        if let Some(on_progress) = &on_progress {
+            if on_progress.failed() {
+                return Err(progress_failed());
+            }
+            if bytes_hashed > 0.0 {
+                let progress = HashProgress {
+                    bytes_hashed,
+                    total_bytes,
+                };
+                on_progress
+                    .function
+                    .call(progress, ThreadsafeFunctionCallMode::NonBlocking);
+            }
+        }
+        hasher.update(&buffer[..length]);
+        bytes_hashed += length as f64;
+    }
+    if let Some(on_progress) = &on_progress {
+        let progress = HashProgress {
+            bytes_hashed,
+            total_bytes,
+        };
+        report_and_wait(on_progress, progress);
+        if on_progress.failed() {
+            return Err(progress_failed());
+        }
+    }
+
+    let digest = hasher.finalize();
+    return Ok(digest.iter().map(|x| format!("{:02x}", x)).collect());
+}
+
+/// The worker's error when the progress callback threw. `HashResult` rejects
+/// with the thrown value instead, so this is never seen from JavaScript.
+fn progress_failed() -> RfdError {
+    return RfdError::new(ErrorCode::Cancelled, "The progress callback threw");
+}
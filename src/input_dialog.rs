@@ -0,0 +1,188 @@
+use super::backend;
+use super::error::*;
+use super::parent_window::*;
+use napi::bindgen_prelude::*;
+
+/// The plain-data configuration of a text input dialog. Shared by the
+/// `InputDialog` and `AsyncInputDialog` builders.
+#[derive(Clone, Default)]
+pub(crate) struct InputOptions {
+    pub(crate) title: Option<String>,
+    pub(crate) description: Option<String>,
+    /// The text that the input starts out with.
+    pub(crate) default_value: Option<String>,
+    /// Masks the input like a password field. Takes precedence over
+    /// `multiline`.
+    pub(crate) password: bool,
+    /// Shows a text area instead of a single line input.
+    pub(crate) multiline: bool,
+    pub(crate) parent: Option<ParentWindow>,
+}
+
+/// The builder for a synchronous text input dialog, the equivalent of the
+/// browser's `prompt()`. It's recommended to use the async `AsyncInputDialog`
+/// builder instead since it lets the Node.js event loop continue even while the
+/// dialog is open.
+///
+/// `rfd` has no input dialog, so on Linux and the BSDs this is shown by
+/// `zenity` (or `kdialog` with `RFD_BACKEND=kdialog`), which has to be
+/// installed. Other platforms throw `ERR_BACKEND_UNAVAILABLE`.
+///
+/// Example:
+///
+/// ```js
+/// const name = new InputDialog()
+///     .setTitle('New folder')
+///     .setDescription('Name of the new folder:')
+///     .setDefaultValue('Untitled')
+///     .show();
+/// console.log(name);
+/// //=> 'Photos'
+/// ```
+#[napi]
+#[repr(transparent)]
+pub struct InputDialog(pub(super) InputOptions);
+impl Default for InputDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[napi]
+impl InputDialog {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        return Self(InputOptions::default());
+    }
+
+    /// Returns a copy of this input dialog with the same settings.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const ask = new InputDialog().setTitle('Rename');
+    /// const name = ask.clone().setDefaultValue('notes.txt').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
+    }
+
+    /// Sets the title of the input dialog. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = new InputDialog().setTitle('Rename').show();
+    /// ```
+    #[napi]
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Sets the text shown above the input. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = new InputDialog()
+    ///     .setDescription('What should the file be called?')
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_description(&mut self, this: This, description: String) -> This {
+        let x = &mut self.0;
+        x.description = Some(description);
+        return this;
+    }
+
+    /// Pre-fills the input. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = new InputDialog().setDefaultValue('notes.txt').show();
+    /// ```
+    #[napi]
+    pub fn set_default_value(&mut self, this: This, value: String) -> This {
+        let x = &mut self.0;
+        x.default_value = Some(value);
+        return this;
+    }
+
+    /// Masks what the user types, for passwords and other secrets. Defaults to
+    /// `false`. A password input is always a single line. Returns `this` for
+    /// chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const password = new InputDialog()
+    ///     .setDescription('Enter the passphrase for id_ed25519:')
+    ///     .setPassword(true)
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_password(&mut self, this: This, password: bool) -> This {
+        let x = &mut self.0;
+        x.password = password;
+        return this;
+    }
+
+    /// Shows a text area that accepts line breaks instead of a single line
+    /// input. Defaults to `false`. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const message = new InputDialog()
+    ///     .setTitle('Commit message')
+    ///     .setMultiline(true)
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_multiline(&mut self, this: This, multiline: bool) -> This {
+        let x = &mut self.0;
+        x.multiline = multiline;
+        return this;
+    }
+
+    /// Makes the input dialog modal for a parent window. See
+    /// `MessageDialog.setParent()` for the accepted handles.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = new InputDialog().setParent(handle).show();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
+    }
+
+    /// Shows the input dialog. Returns the text that was entered, or `null` if
+    /// the dialog was cancelled.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const name = new InputDialog().setDescription('Your name:').show();
+    /// if (name !== null) {
+    ///   console.log(`Hello, ${name}!`);
+    /// }
+    /// ```
+    #[napi(ts_return_type = "string | null")]
+    pub fn show(&self) -> SyncResult<Option<String>> {
+        let x = &self.0;
+        let y = backend::input_dialog(x);
+        return SyncResult(y);
+    }
+}
@@ -0,0 +1,83 @@
+use super::error::*;
+use napi::bindgen_prelude::*;
+use serde_json::{Number, Value};
+use std::path::Path;
+
+/// A parsed JSON document. Parsing happens on a worker thread with `parse()`,
+/// so only the JavaScript values are created on the main thread.
+pub struct Json(Value);
+impl ToNapiValue for Json {
+    unsafe fn to_napi_value(env: sys::napi_env, val: Self) -> Result<sys::napi_value> {
+        return Value::to_napi_value(env, val.0);
+    }
+}
+
+/// `JSON.parse()` turns integers that don't fit in a double into imprecise
+/// numbers, where napi would create a `BigInt`.
+fn to_doubles(value: &mut Value) {
+    match value {
+        Value::Number(number) if number.as_f64().is_some() && !number.is_f64() => {
+            let double = number.as_f64().unwrap();
+            if double.abs() > 9007199254740991.0 {
+                *number = Number::from_f64(double).unwrap();
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(to_doubles),
+        Value::Object(values) => values.values_mut().for_each(to_doubles),
+        _ => {}
+    }
+}
+
+/// Turns the position of a `serde_json` error into a 1-based line and column
+/// counted in characters. `serde_json` counts the column in bytes and reports
+/// a mistake at a line break as column 0 of the next line.
+fn position(text: &str, error: &serde_json::Error) -> (usize, usize) {
+    let mut line = error.line();
+    let lines: Vec<&str> = text.split('\n').collect();
+    if error.column() == 0 && line > 1 {
+        line -= 1;
+        let column = lines[line - 1].chars().count() + 1;
+        return (line, column);
+    }
+    let column = lines.get(line - 1).map_or(error.column(), |x| {
+        return x
+            .char_indices()
+            .take_while(|(i, _)| *i < error.column())
+            .count();
+    });
+    return (line, column);
+}
+
+/// Parses `text` as JSON. Otherwise, returns an `ERR_INVALID_DATA` error with
+/// the 1-based `line` and `column` (counted in characters) of the first
+/// mistake in the file at `path`.
+pub(crate) fn parse(text: &str, path: &Path) -> RfdResult<Json> {
+    let result = serde_json::from_str::<Value>(text);
+    if result.is_ok() {
+        let mut value = result.unwrap();
+        to_doubles(&mut value);
+        return Ok(Json(value));
+    }
+    let error = result.unwrap_err();
+    let (line, column) = position(text, &error);
+    // Without the position, which is replaced by the one from `position()`.
+    let mut message = error.to_string();
+    if let Some(i) = message.rfind(" at line ") {
+        message.truncate(i);
+    }
+    let mut rfd_error = RfdError::new(
+        ErrorCode::InvalidData,
+        format!(
+            "{} in JSON at line {} column {}, '{}'",
+            message,
+            line,
+            column,
+            path.to_string_lossy()
+        ),
+    );
+    let details = rfd_error.details_mut();
+    details.path = Some(path.to_string_lossy().into_owned());
+    details.line = Some(line as u32);
+    details.column = Some(column as u32);
+    return Err(rfd_error);
+}
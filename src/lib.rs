@@ -1,18 +1,72 @@
 #[macro_use]
 extern crate napi_derive;
 
+mod abort;
+mod async_color_dialog;
 mod async_file_dialog;
+mod async_font_dialog;
+mod async_input_dialog;
 mod async_message_dialog;
+mod backend;
+mod backend_name;
+mod capabilities;
+mod closable;
+mod color_dialog;
+mod dialog_options;
+mod error;
+mod file_cursor;
 mod file_dialog;
 mod file_handle;
+mod file_stat;
+mod file_stream;
+mod file_watcher;
+mod font_dialog;
+mod hash;
+mod input_dialog;
+mod json;
+mod line_stream;
 mod message_buttons;
 mod message_dialog;
+mod message_dialog_result;
 mod message_level;
+mod mime;
+mod mock;
+mod notification;
+#[cfg(freedesktop)]
+mod notifications;
+mod parent_window;
+mod path_encoding;
+#[cfg(freedesktop)]
+mod portal;
+mod subprocess;
+mod text;
 
+pub use abort::*;
+pub use async_color_dialog::*;
 pub use async_file_dialog::*;
+pub use async_font_dialog::*;
+pub use async_input_dialog::*;
 pub use async_message_dialog::*;
+pub use backend_name::*;
+pub use capabilities::*;
+pub use color_dialog::*;
+pub use dialog_options::*;
+pub use error::*;
+pub use file_cursor::*;
 pub use file_dialog::*;
 pub use file_handle::*;
+pub use file_stat::*;
+pub use file_stream::*;
+pub use file_watcher::*;
+pub use font_dialog::*;
+pub use hash::*;
+pub use input_dialog::*;
+pub use line_stream::*;
 pub use message_buttons::*;
 pub use message_dialog::*;
+pub use message_dialog_result::*;
 pub use message_level::*;
+pub use mock::*;
+pub use notification::*;
+pub use path_encoding::*;
+pub use text::*;
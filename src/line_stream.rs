@@ -0,0 +1,223 @@
+use super::closable::Closable;
+use super::error::*;
+use super::file_stream::make_async_iterable;
+use super::text::*;
+use napi::bindgen_prelude::*;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The `{ done, value }` object produced by `LineStream.next()`.
+#[napi(object)]
+pub struct LineStreamResult {
+    pub done: bool,
+    pub value: Option<String>,
+}
+
+struct LineStreamState {
+    reader: Option<BufReader<File>>,
+    /// Picked when the file is opened since it may depend on the byte order
+    /// mark.
+    encoding: Option<TextEncoding>,
+    done: bool,
+}
+
+/// Reads up to and including the next `\n` in UTF-16 into `line`. Returns
+/// the number of bytes read, which is 0 at the end of the file.
+fn read_utf16_line(
+    reader: &mut BufReader<File>,
+    encoding: TextEncoding,
+    line: &mut Vec<u8>,
+) -> std::io::Result<usize> {
+    let newline = match encoding {
+        TextEncoding::Utf16Le => [b'\n', 0],
+        _ => [0, b'\n'],
+    };
+    let mut unit = [0u8; 2];
+    loop {
+        let length = reader.read(&mut unit[..1])?;
+        if length == 0 {
+            return Ok(line.len());
+        }
+        let length = reader.read(&mut unit[1..])?;
+        line.extend_from_slice(&unit[..1 + length]);
+        if length == 0 || unit == newline {
+            return Ok(line.len());
+        }
+    }
+}
+
+/// Removes the trailing `\n` or `\r\n` of a line in the given encoding.
+fn trim_newline(line: &mut Vec<u8>, encoding: TextEncoding) {
+    let (newline, carriage_return): (&[u8], &[u8]) = match encoding {
+        TextEncoding::Utf16Le => (b"\n\0", b"\r\0"),
+        TextEncoding::Utf16Be => (b"\0\n", b"\0\r"),
+        _ => (b"\n", b"\r"),
+    };
+    if line.ends_with(newline) {
+        line.truncate(line.len() - newline.len());
+        if line.ends_with(carriage_return) {
+            line.truncate(line.len() - carriage_return.len());
+        }
+    }
+}
+
+/// An async iterator over the lines of a text file. Get one from
+/// `FileHandle.lines()` and use it with `for await`. The file is read and
+/// decoded on a worker thread a line at a time, so the whole file never has
+/// to be in memory. Lines end at `\n` or `\r\n`, which isn't part of the
+/// line, like `readline` does. Breaking out of the loop or calling `.cancel()`
+/// closes the underlying file.
+///
+/// Example:
+///
+/// ```js
+/// const fileHandle = await new AsyncFileDialog().pickFile();
+/// let errors = 0;
+/// for await (const line of fileHandle.lines()) {
+///   if (line.includes('ERROR')) {
+///     errors++;
+///   }
+/// }
+/// console.log(errors);
+/// //=> 3
+/// ```
+#[napi]
+pub struct LineStream {
+    path: PathBuf,
+    state: Arc<Closable<LineStreamState>>,
+}
+#[napi]
+impl LineStream {
+    pub(crate) fn new(path: PathBuf, options: Option<TextOptions>) -> Self {
+        return Self {
+            path,
+            state: Arc::new(Closable::new(LineStreamState {
+                reader: None,
+                encoding: options.and_then(|x| x.encoding),
+                done: false,
+            })),
+        };
+    }
+
+    /// Creates the JavaScript object for this stream and makes it usable with
+    /// `for await`.
+    pub(crate) fn into_async_iterator(self, env: Env) -> Result<ClassInstance<Self>> {
+        let instance = self.into_instance(env)?;
+        make_async_iterable(env, instance.as_object(env))?;
+        return Ok(instance);
+    }
+
+    /// Reads and decodes the next line. Resolves to `{ done: true }` at the
+    /// end of the file or once the stream was cancelled.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const lines = fileHandle.lines();
+    /// const { value: header } = await lines.next();
+    /// ```
+    #[napi(ts_return_type = "Promise<LineStreamResult>")]
+    pub async unsafe fn next(&self) -> AsyncResult<LineStreamResult> {
+        let state = self.state.clone();
+        let path = self.path.clone();
+        let line = spawn_io(move || {
+            let line = state.with(|state| read_line(&path, state));
+            return line.unwrap_or(Ok(None));
+        })
+        .await;
+        return match line {
+            Ok(None) => AsyncResult(Ok(LineStreamResult {
+                done: true,
+                value: None,
+            })),
+            Ok(Some(line)) => AsyncResult(Ok(LineStreamResult {
+                done: false,
+                value: Some(line),
+            })),
+            Err(error) => AsyncResult(Err(error)),
+        };
+    }
+
+    /// Stops reading and closes the file. A pending `.next()` and any further
+    /// ones resolve to `{ done: true }`. It returns right away even while a
+    /// read is in progress; the file is closed once that read is done. This is
+    /// called for you when you `break` out of a `for await` loop.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const lines = fileHandle.lines();
+    /// await lines.next();
+    /// lines.cancel();
+    /// ```
+    #[napi]
+    pub fn cancel(&self) {
+        self.state.close();
+    }
+
+    /// The async iterator protocol's `return()` method. Same as `.cancel()`.
+    #[napi(js_name = "return")]
+    pub async unsafe fn return_(&self) -> LineStreamResult {
+        self.cancel();
+        return LineStreamResult {
+            done: true,
+            value: None,
+        };
+    }
+}
+
+fn open(path: &Path, state: &mut LineStreamState) -> RfdResult<()> {
+    let file = File::open(path);
+    if file.is_err() {
+        return Err(RfdError::io(file.unwrap_err(), "open", path));
+    }
+    let mut reader = BufReader::new(file.unwrap());
+    let head = reader.fill_buf();
+    if head.is_err() {
+        return Err(RfdError::io(head.unwrap_err(), "read", path));
+    }
+    let (encoding, bom) = detect(head.unwrap(), state.encoding);
+    reader.consume(bom);
+    state.encoding = Some(encoding);
+    state.reader = Some(reader);
+    return Ok(());
+}
+
+/// Reads and decodes the next line on a worker thread, or `None` at the
+/// end.
+fn read_line(path: &Path, state: &mut LineStreamState) -> RfdResult<Option<String>> {
+    if state.done {
+        return Ok(None);
+    }
+    if state.reader.is_none() {
+        let result = open(path, state);
+        if result.is_err() {
+            state.done = true;
+            return Err(result.unwrap_err());
+        }
+    }
+
+    let encoding = state.encoding.unwrap();
+    let reader = state.reader.as_mut().unwrap();
+    let mut line = Vec::new();
+    let result = match encoding {
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            read_utf16_line(reader, encoding, &mut line)
+        }
+        _ => reader.read_until(b'\n', &mut line),
+    };
+    if result.is_err() {
+        state.done = true;
+        state.reader = None;
+        return Err(RfdError::io(result.unwrap_err(), "read", path));
+    }
+    if line.is_empty() {
+        state.done = true;
+        state.reader = None;
+        return Ok(None);
+    }
+    trim_newline(&mut line, encoding);
+    return Ok(Some(decode(&line, encoding)));
+}
@@ -1,20 +1,104 @@
-use napi::bindgen_prelude::*;
+use super::error::*;
 use rfd;
 
 /// Used by the `MessageDialog` and `AsyncMessageDialog` as sentinels for
-/// specific button configurations.
+/// specific button configurations. The `...Custom` variants take their button
+/// labels from the second argument of `.setButtons()`: one label for
+/// `OkCustom`, two for `OkCancelCustom` and three for `YesNoCancelCustom`.
+///
+/// Example:
+///
+/// ```js
+/// const result = await new AsyncMessageDialog()
+///     .setTitle('Save changes?')
+///     .setButtons('YesNoCancelCustom', ['Save', "Don't Save", 'Cancel'])
+///     .show();
+/// ```
 #[napi(string_enum)]
 pub enum MessageButtons {
     Ok,
     OkCancel,
     YesNo,
+    YesNoCancel,
+    OkCustom,
+    OkCancelCustom,
+    YesNoCancelCustom,
 }
 impl MessageButtons {
-    pub(super) fn to_rfd_t(&self) -> rfd::MessageButtons {
+    /// How many custom labels this button configuration needs.
+    pub(crate) fn label_count(&self) -> usize {
+        match self {
+            MessageButtons::OkCustom => 1,
+            MessageButtons::OkCancelCustom => 2,
+            MessageButtons::YesNoCancelCustom => 3,
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn check_labels(&self, labels: &[String]) -> RfdResult<()> {
+        if labels.len() != self.label_count() {
+            return Err(RfdError::new(
+                ErrorCode::InvalidArg,
+                format!(
+                    "{} buttons need {} labels, got {}",
+                    self.name(),
+                    self.label_count(),
+                    labels.len()
+                ),
+            ));
+        }
+        return Ok(());
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            MessageButtons::Ok => "Ok",
+            MessageButtons::OkCancel => "OkCancel",
+            MessageButtons::YesNo => "YesNo",
+            MessageButtons::YesNoCancel => "YesNoCancel",
+            MessageButtons::OkCustom => "OkCustom",
+            MessageButtons::OkCancelCustom => "OkCancelCustom",
+            MessageButtons::YesNoCancelCustom => "YesNoCancelCustom",
+        }
+    }
+
+    /// The result of clicking the affirmative button (or the negative one when
+    /// `affirmative` is `false`). Used by the mock and subprocess backends.
+    pub(crate) fn button_result(&self, labels: &[String], affirmative: bool) -> String {
+        let result = match (self, affirmative) {
+            (MessageButtons::Ok | MessageButtons::OkCancel, true) => "Ok",
+            (MessageButtons::YesNo | MessageButtons::YesNoCancel, true) => "Yes",
+            (MessageButtons::YesNo | MessageButtons::YesNoCancel, false) => "No",
+            (
+                MessageButtons::OkCustom
+                | MessageButtons::OkCancelCustom
+                | MessageButtons::YesNoCancelCustom,
+                true,
+            ) => &labels[0],
+            (MessageButtons::OkCancelCustom | MessageButtons::YesNoCancelCustom, false) => {
+                &labels[1]
+            }
+            _ => "Cancel",
+        };
+        return result.to_string();
+    }
+
+    /// The labels must already have been checked with `check_labels()`.
+    pub(super) fn to_rfd_t(&self, labels: &[String]) -> rfd::MessageButtons {
         match self {
             MessageButtons::Ok => rfd::MessageButtons::Ok,
             MessageButtons::OkCancel => rfd::MessageButtons::OkCancel,
             MessageButtons::YesNo => rfd::MessageButtons::YesNo,
+            MessageButtons::YesNoCancel => rfd::MessageButtons::YesNoCancel,
+            MessageButtons::OkCustom => rfd::MessageButtons::OkCustom(labels[0].clone()),
+            MessageButtons::OkCancelCustom => {
+                rfd::MessageButtons::OkCancelCustom(labels[0].clone(), labels[1].clone())
+            }
+            MessageButtons::YesNoCancelCustom => rfd::MessageButtons::YesNoCancelCustom(
+                labels[0].clone(),
+                labels[1].clone(),
+                labels[2].clone(),
+            ),
         }
     }
 }
@@ -1,8 +1,90 @@
+use super::backend;
+use super::error::*;
 use super::message_buttons::*;
+use super::message_dialog_result::*;
 use super::message_level::*;
+use super::parent_window::*;
 use napi::bindgen_prelude::*;
 use rfd;
 
+/// The plain-data configuration of a message dialog. Shared by the
+/// `MessageDialog` and `AsyncMessageDialog` builders. Unset fields fall back to
+/// the `rfd` defaults.
+#[derive(Clone, Default)]
+pub(crate) struct MessageOptions {
+    pub(crate) level: Option<MessageLevel>,
+    pub(crate) title: Option<String>,
+    pub(crate) description: Option<String>,
+    pub(crate) buttons: Option<MessageButtons>,
+    /// The custom button labels for the `...Custom` buttons.
+    pub(crate) labels: Vec<String>,
+    pub(crate) parent: Option<ParentWindow>,
+    /// How many milliseconds to wait for an answer, and the result to use when
+    /// nobody answered in time.
+    pub(crate) timeout: Option<(u32, String)>,
+}
+impl MessageOptions {
+    /// Fills in the default result for a dialog that timed out, which the
+    /// backends report as `None`.
+    pub(crate) fn response(&self, result: Option<String>) -> MessageDialogResponse {
+        if let Some(result) = result {
+            return MessageDialogResponse {
+                result,
+                timed_out: false,
+            };
+        }
+        let default_result = self.timeout.as_ref().map(|x| x.1.clone());
+        return MessageDialogResponse {
+            result: default_result.unwrap_or_else(|| "Cancel".to_string()),
+            timed_out: true,
+        };
+    }
+
+    pub(crate) fn to_rfd_t(&self) -> rfd::MessageDialog {
+        let mut x = rfd::MessageDialog::new();
+        if let Some(level) = &self.level {
+            x = x.set_level(level.to_rfd_t());
+        }
+        if let Some(title) = &self.title {
+            x = x.set_title(title);
+        }
+        if let Some(description) = &self.description {
+            x = x.set_description(description);
+        }
+        if let Some(buttons) = &self.buttons {
+            x = x.set_buttons(buttons.to_rfd_t(&self.labels));
+        }
+        // `rfd` can't use Wayland handles, and its GTK dialogs ignore the
+        // parent altogether. See `setParent()`.
+        if let Some(parent) = self.parent.as_ref().filter(|x| x.is_raw()) {
+            x = x.set_parent(parent);
+        }
+        return x;
+    }
+
+    pub(crate) fn to_rfd_async_t(&self) -> rfd::AsyncMessageDialog {
+        let mut x = rfd::AsyncMessageDialog::new();
+        if let Some(level) = &self.level {
+            x = x.set_level(level.to_rfd_t());
+        }
+        if let Some(title) = &self.title {
+            x = x.set_title(title);
+        }
+        if let Some(description) = &self.description {
+            x = x.set_description(description);
+        }
+        if let Some(buttons) = &self.buttons {
+            x = x.set_buttons(buttons.to_rfd_t(&self.labels));
+        }
+        // `rfd` can't use Wayland handles, and its GTK dialogs ignore the
+        // parent altogether. See `setParent()`.
+        if let Some(parent) = self.parent.as_ref().filter(|x| x.is_raw()) {
+            x = x.set_parent(parent);
+        }
+        return x;
+    }
+}
+
 /// The builder for a synchronous message dialog popup window. Use this to show
 /// alert boxes and other messages to the user. It's recommended to use the async
 /// `AsyncMessageDialog` builder instead since it lets the Node.js event loop
@@ -18,16 +100,36 @@ use rfd;
 ///     .setButtons('Ok')
 ///     .show();
 /// console.log(answer);
-/// //=> true
+/// //=> 'Ok'
 /// ```
 #[napi]
 #[repr(transparent)]
-pub struct MessageDialog(pub(super) Option<rfd::MessageDialog>);
+pub struct MessageDialog(pub(super) MessageOptions);
+impl Default for MessageDialog {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
 #[napi]
 impl MessageDialog {
     #[napi(constructor)]
     pub fn new() -> Self {
-        return Self(Some(rfd::MessageDialog::new()));
+        return Self(MessageOptions::default());
+    }
+
+    /// Returns a copy of this message dialog with the same level, title,
+    /// description and buttons.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const warning = new MessageDialog().setLevel('Warning');
+    /// warning.clone().setDescription('Disk is almost full.').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
     }
 
     /// Sets the level of the message dialog. This determines the icon that is
@@ -42,13 +144,10 @@ impl MessageDialog {
     ///     .show();
     /// ```
     #[napi]
-    pub fn set_level(&mut self, level: MessageLevel) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_level(level.to_rfd_t());
-        return Ok(Self(Some(x)));
+    pub fn set_level(&mut self, this: This, level: MessageLevel) -> This {
+        let x = &mut self.0;
+        x.level = Some(level);
+        return this;
     }
 
     /// Sets the title of the message dialog. Defaults to an empty string.
@@ -62,13 +161,38 @@ impl MessageDialog {
     ///     .show();
     /// ```
     #[napi]
-    pub fn set_title(&mut self, title: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_title(&title);
-        return Ok(Self(Some(x)));
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Makes the message box modal for a parent window. The handle is an X11
+    /// window id on Linux, an `HWND` on Windows or an `NSWindow*` on macOS, as
+    /// a number or bigint. Wayland surface handle strings are accepted too.
+    ///
+    /// On Linux and the BSDs, it's ignored by the `gtk` and `portal` backends,
+    /// and Wayland handles are ignored everywhere since only the file chooser
+    /// portal takes them. `zenity` and `kdialog` honour X11 window ids. Check
+    /// `capabilities().messageDialog.parent`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const answer = new MessageDialog()
+    ///     .setParent(handle)
+    ///     .setTitle('Quit?')
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn set_parent(
+        &mut self,
+        this: This,
+        handle: Either3<i64, BigInt, String>,
+    ) -> Result<This, ErrorCode> {
+        let x = &mut self.0;
+        x.parent = Some(ParentWindow::from_js(handle)?);
+        return Ok(this);
     }
 
     /// Sets the description of the message dialog. Defaults to an empty string.
@@ -82,53 +206,105 @@ impl MessageDialog {
     ///     .show();
     /// ```
     #[napi]
-    pub fn set_description(&mut self, description: String) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_description(&description);
-        return Ok(Self(Some(x)));
+    pub fn set_description(&mut self, this: This, description: String) -> This {
+        let x = &mut self.0;
+        x.description = Some(description);
+        return this;
     }
 
     /// Sets the buttons that are shown in the message dialog. Defaults to `Ok`.
-    /// Returns `this` for chaining.
+    /// The `OkCustom`, `OkCancelCustom` and `YesNoCancelCustom` buttons need
+    /// one, two or three `labels` respectively. Throws an `ERR_INVALID_ARG`
+    /// error if the number of labels doesn't match. Returns `this` for
+    /// chaining.
     ///
     /// Example:
     ///
     /// ```js
     /// const answer = new MessageDialog()
-    ///     .setButtons('OkCancel')
+    ///     .setButtons('OkCancelCustom', ['Retry', 'Give up'])
     ///     .show();
+    /// console.log(answer);
+    /// //=> 'Retry'
     /// ```
     #[napi]
-    pub fn set_buttons(&mut self, buttons: MessageButtons) -> Result<Self> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let x = x.set_buttons(buttons.to_rfd_t());
-        return Ok(Self(Some(x)));
+    pub fn set_buttons(
+        &mut self,
+        this: This,
+        buttons: MessageButtons,
+        labels: Option<Vec<String>>,
+    ) -> Result<This, ErrorCode> {
+        let labels = labels.unwrap_or_default();
+        buttons.check_labels(&labels)?;
+        let x = &mut self.0;
+        x.buttons = Some(buttons);
+        x.labels = labels;
+        return Ok(this);
     }
 
-    /// Shows the message dialog. Returns `true` if the user clicked the
-    /// affirmative button, `false` otherwise.
+    /// Closes the dialog after `ms` milliseconds without an answer and returns
+    /// `defaultResult` from `.show()` instead. Use `.showDetailed()` to find out
+    /// whether that happened. Only the `zenity`, `kdialog` and `mock` backends
+    /// can close a dialog (see `capabilities()`). The native dialogs of this
+    /// blocking builder wait for an answer; `AsyncMessageDialog` resolves to
+    /// `defaultResult` on time but leaves them open. Returns `this` for
+    /// chaining.
     ///
     /// Example:
     ///
     /// ```js
     /// const answer = new MessageDialog()
+    ///     .setDescription('Install updates now?')
+    ///     .setButtons('YesNo')
+    ///     .setTimeout(60_000, 'Yes')
     ///     .show();
-    /// console.log(answer);
-    /// //=> true
     /// ```
     #[napi]
-    pub fn show(&mut self) -> Result<bool> {
-        if self.0.is_none() {
-            return Err(Error::from_reason("Already used"));
-        }
-        let x = self.0.take().unwrap();
-        let y = x.show();
-        return Ok(y);
+    pub fn set_timeout(
+        &mut self,
+        this: This,
+        ms: u32,
+        #[napi(ts_arg_type = "MessageDialogResult | string")] default_result: String,
+    ) -> This {
+        let x = &mut self.0;
+        x.timeout = Some((ms, default_result));
+        return this;
+    }
+
+    /// Shows the message dialog. Returns which button was clicked: one of the
+    /// `MessageDialogResult` values (`'Cancel'` if the dialog was dismissed) or
+    /// the label of a custom button.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const answer = new MessageDialog()
+    ///     .setButtons('YesNo')
+    ///     .show();
+    /// console.log(answer);
+    /// //=> 'Yes'
+    /// ```
+    #[napi(ts_return_type = "MessageDialogResult | string")]
+    pub fn show(&self) -> SyncResult<String> {
+        let x = &self.0;
+        let y = backend::message_dialog(x);
+        return SyncResult(y.map(|y| x.response(y).result));
+    }
+
+    /// Same as `.show()` but also says whether the dialog timed out. See
+    /// `.setTimeout()`.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const { result, timedOut } = new MessageDialog()
+    ///     .setTimeout(10_000, 'Ok')
+    ///     .showDetailed();
+    /// ```
+    #[napi(ts_return_type = "MessageDialogResponse")]
+    pub fn show_detailed(&self) -> SyncResult<MessageDialogResponse> {
+        let x = &self.0;
+        let y = backend::message_dialog(x);
+        return SyncResult(y.map(|y| x.response(y)));
     }
 }
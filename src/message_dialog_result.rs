@@ -0,0 +1,69 @@
+use rfd;
+
+/// What `MessageDialog.show()` and `AsyncMessageDialog.show()` return for the
+/// built-in buttons. Dismissing the dialog (with Escape or the window's close
+/// button) counts as `'Cancel'`. Dialogs using the `...Custom` buttons return
+/// the label of the button that was clicked instead.
+///
+/// Example:
+///
+/// ```js
+/// const result = new MessageDialog().setButtons('YesNoCancel').show();
+/// if (result === MessageDialogResult.Yes) {
+///   save();
+/// }
+/// ```
+#[napi(string_enum)]
+pub enum MessageDialogResult {
+    Yes,
+    No,
+    Ok,
+    Cancel,
+}
+impl AsRef<str> for MessageDialogResult {
+    fn as_ref(&self) -> &str {
+        match self {
+            MessageDialogResult::Yes => "Yes",
+            MessageDialogResult::No => "No",
+            MessageDialogResult::Ok => "Ok",
+            MessageDialogResult::Cancel => "Cancel",
+        }
+    }
+}
+
+/// What `MessageDialog.showDetailed()` and `AsyncMessageDialog.showDetailed()`
+/// return. Use it to tell a dialog that timed out apart from one where the user
+/// happened to click the default button.
+///
+/// Example:
+///
+/// ```js
+/// const { result, timedOut } = new MessageDialog()
+///     .setButtons('YesNo')
+///     .setTimeout(30_000, 'Yes')
+///     .showDetailed();
+/// if (timedOut) {
+///   console.log(`Nobody answered, continuing with ${result}`);
+/// }
+/// ```
+#[napi(object)]
+pub struct MessageDialogResponse {
+    /// The same value that `.show()` returns.
+    #[napi(ts_type = "MessageDialogResult | string")]
+    pub result: String,
+    /// `true` when the `.setTimeout()` ran out before a button was clicked. The
+    /// `result` is the default result in that case.
+    pub timed_out: bool,
+}
+
+/// Turns the `rfd` result into the string that is returned to JavaScript.
+pub(crate) fn result_from_rfd_t(result: rfd::MessageDialogResult) -> String {
+    let result = match result {
+        rfd::MessageDialogResult::Yes => MessageDialogResult::Yes,
+        rfd::MessageDialogResult::No => MessageDialogResult::No,
+        rfd::MessageDialogResult::Ok => MessageDialogResult::Ok,
+        rfd::MessageDialogResult::Cancel => MessageDialogResult::Cancel,
+        rfd::MessageDialogResult::Custom(label) => return label,
+    };
+    return result.as_ref().to_string();
+}
@@ -1,4 +1,3 @@
-use napi::bindgen_prelude::*;
 use rfd;
 
 /// Used by the `MessageDialog` and `AsyncMessageDialog` as sentinels for
@@ -0,0 +1,56 @@
+use super::error::*;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// How many bytes from the start of the file are sniffed. `infer` finds most
+/// formats in the first few bytes, `tar` at offset 257 and the zip-based
+/// office formats by the names of their first entries.
+const SNIFF_LEN: usize = 8192;
+
+/// Whether the bytes look like text: valid UTF-8 (allowing a character that
+/// was cut off at the end) without any NUL bytes.
+fn is_text(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return false;
+    }
+    return match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(x) => x.error_len().is_none(),
+    };
+}
+
+/// Guesses the MIME type of `path` from its first bytes with `infer`, falling
+/// back to its extension with `mime_guess`. Folders are `inode/directory`.
+pub(crate) fn mime_type(path: &Path) -> RfdResult<String> {
+    // Before opening it, since folders can't be opened on Windows.
+    let metadata = fs::metadata(path);
+    if metadata.is_err() {
+        return Err(RfdError::io(metadata.unwrap_err(), "stat", path));
+    }
+    if metadata.unwrap().is_dir() {
+        return Ok("inode/directory".to_string());
+    }
+    let file = fs::File::open(path);
+    if file.is_err() {
+        return Err(RfdError::io(file.unwrap_err(), "open", path));
+    }
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    let result = file.unwrap().take(SNIFF_LEN as u64).read_to_end(&mut head);
+    if result.is_err() {
+        return Err(RfdError::io(result.unwrap_err(), "read", path));
+    }
+    let extension = mime_guess::from_path(path).first_raw();
+    let mime = match infer::get(&head).map(|x| x.mime_type()) {
+        // Office documents, EPUBs and JARs are zip files. `infer` only tells
+        // them apart when the right entry comes first.
+        Some("application/zip") if extension.is_some_and(|x| x != "application/octet-stream") => {
+            extension.unwrap()
+        }
+        Some(mime) => mime,
+        None if extension.is_some() => extension.unwrap(),
+        None if !head.is_empty() && is_text(&head) => "text/plain",
+        None => "application/octet-stream",
+    };
+    return Ok(mime.to_string());
+}
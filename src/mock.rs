@@ -0,0 +1,475 @@
+use super::backend::{self, Backend};
+use super::color_dialog::*;
+use super::error::*;
+use super::file_dialog::*;
+use super::font_dialog::*;
+use super::input_dialog::*;
+use super::message_buttons::*;
+use super::message_dialog::*;
+use super::message_level::*;
+use super::notification::*;
+use super::path_encoding::*;
+use napi::bindgen_prelude::*;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A record of a single dialog that was requested while the mock backend was
+/// installed. Only the fields that make sense for that kind of dialog are set.
+/// File dialogs fill in `title`, `directory`, `fileName` and `filters` (plus
+/// `fileNames` for `'saveFiles'`), while message dialogs fill in `title`,
+/// `description`, `level`, `buttons` and `timeout`, and input dialogs fill in
+/// `title`, `description`, `defaultValue`, `password` and `multiline`. Color
+/// dialogs fill in `title`, `color` and `alpha`, and font dialogs fill in
+/// `title`, `family`, `style`, `weight` and `size`. Notifications fill in
+/// `title`, `description` (the body), `level` (the urgency), `icon`, `actions`
+/// and `timeout`.
+///
+/// Example:
+///
+/// ```js
+/// mock.install();
+/// mock.pushPaths(['/tmp/hello.txt']);
+/// await new AsyncFileDialog().setTitle('Open a file').pickFile();
+/// console.log(mock.requests());
+/// //=> [{ method: 'pickFile', title: 'Open a file', filters: [] }]
+/// ```
+#[napi(object)]
+#[derive(Clone, Default)]
+pub struct MockRequest {
+    /// The name of the terminal method that opened the dialog like
+    /// `'pickFile'`, `'saveFile'` or `'show'`.
+    pub method: String,
+    pub title: Option<String>,
+    pub directory: Option<String>,
+    pub file_name: Option<String>,
+    pub file_names: Option<Vec<String>>,
+    pub filters: Option<Vec<FileFilter>>,
+    pub description: Option<String>,
+    pub level: Option<MessageLevel>,
+    pub buttons: Option<MessageButtons>,
+    pub labels: Option<Vec<String>>,
+    /// The parent window from `.setParent()` like `'x11:0x3a00007'`,
+    /// `'wayland:…'`, `'win32:0x…'` or `'appkit:0x…'`.
+    pub parent: Option<String>,
+    /// The milliseconds from `.setTimeout()`.
+    pub timeout: Option<u32>,
+    pub default_value: Option<String>,
+    pub password: Option<bool>,
+    pub multiline: Option<bool>,
+    /// The initial color from `.setColor()` as `#rrggbbaa`.
+    pub color: Option<String>,
+    pub alpha: Option<bool>,
+    pub family: Option<String>,
+    pub style: Option<FontStyle>,
+    pub weight: Option<u32>,
+    /// The initial size from `.setSize()` in points.
+    pub size: Option<f64>,
+    /// The icon path from `Notification.setIcon()`.
+    pub icon: Option<String>,
+    pub actions: Option<Vec<NotificationAction>>,
+}
+
+enum MockAnswer {
+    Paths(Vec<PathBuf>),
+    Button(Either<bool, String>),
+    Cancel,
+    Timeout,
+    Text(String),
+    Color(Rgba),
+    Font(Font),
+}
+
+struct MockState {
+    answers: VecDeque<MockAnswer>,
+    requests: Vec<MockRequest>,
+}
+
+static STATE: Mutex<MockState> = Mutex::new(MockState {
+    answers: VecDeque::new(),
+    requests: Vec::new(),
+});
+
+fn next_answer(request: MockRequest) -> RfdResult<MockAnswer> {
+    let mut state = STATE.lock().unwrap();
+    let method = request.method.clone();
+    state.requests.push(request);
+    let answer = state.answers.pop_front();
+    if answer.is_none() {
+        return Err(RfdError::new(
+            ErrorCode::BackendUnavailable,
+            format!("No mock answer queued for {}()", method),
+        ));
+    }
+    return Ok(answer.unwrap());
+}
+
+pub(crate) fn file_dialog(
+    method: FileMethod,
+    options: &FileOptions,
+) -> RfdResult<Option<Vec<PathBuf>>> {
+    let answer = next_answer(MockRequest {
+        method: method.name().to_string(),
+        title: options.title.clone(),
+        directory: options.directory.clone(),
+        file_name: options.file_name.clone(),
+        file_names: match method {
+            FileMethod::SaveFiles => Some(options.file_names.clone()),
+            _ => None,
+        },
+        filters: Some(options.filters.clone()),
+        parent: options.parent.as_ref().map(|x| x.describe()),
+        ..Default::default()
+    })?;
+    match answer {
+        MockAnswer::Paths(paths) => Ok(Some(paths)),
+        MockAnswer::Cancel => Ok(None),
+        MockAnswer::Button(_) => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            format!("Mock answer for {}() is a button, not paths", method.name()),
+        )),
+        MockAnswer::Text(_) | MockAnswer::Color(_) | MockAnswer::Font(_) => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            format!("Mock answer for {}() is not paths", method.name()),
+        )),
+        MockAnswer::Timeout => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            format!("File dialogs like {}() can't time out", method.name()),
+        )),
+    }
+}
+
+pub(crate) fn message_dialog(options: &MessageOptions) -> RfdResult<Option<String>> {
+    let answer = next_answer(MockRequest {
+        method: "show".to_string(),
+        title: options.title.clone(),
+        description: options.description.clone(),
+        level: options.level,
+        buttons: options.buttons,
+        labels: Some(options.labels.clone()),
+        parent: options.parent.as_ref().map(|x| x.describe()),
+        timeout: options.timeout.as_ref().map(|x| x.0),
+        ..Default::default()
+    })?;
+    match answer {
+        MockAnswer::Button(Either::A(affirmative)) => {
+            let buttons = options.buttons.unwrap_or(MessageButtons::Ok);
+            Ok(Some(buttons.button_result(&options.labels, affirmative)))
+        }
+        MockAnswer::Button(Either::B(result)) => Ok(Some(result)),
+        MockAnswer::Cancel => Ok(Some("Cancel".to_string())),
+        MockAnswer::Timeout if options.timeout.is_none() => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            "Mock answer for show() is a timeout, but the dialog has no setTimeout()",
+        )),
+        MockAnswer::Timeout => Ok(None),
+        MockAnswer::Paths(_) => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            "Mock answer for show() is paths, not a button",
+        )),
+        MockAnswer::Text(_) | MockAnswer::Color(_) | MockAnswer::Font(_) => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            "Mock answer for show() is not a button",
+        )),
+    }
+}
+
+pub(crate) fn input_dialog(options: &InputOptions) -> RfdResult<Option<String>> {
+    let answer = next_answer(MockRequest {
+        method: "show".to_string(),
+        title: options.title.clone(),
+        description: options.description.clone(),
+        parent: options.parent.as_ref().map(|x| x.describe()),
+        default_value: options.default_value.clone(),
+        password: Some(options.password),
+        multiline: Some(options.multiline),
+        ..Default::default()
+    })?;
+    match answer {
+        MockAnswer::Text(text) => Ok(Some(text)),
+        MockAnswer::Cancel => Ok(None),
+        _ => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            "Mock answer for an input dialog's show() is not text",
+        )),
+    }
+}
+
+pub(crate) fn color_dialog(options: &ColorOptions) -> RfdResult<Option<Rgba>> {
+    let answer = next_answer(MockRequest {
+        method: "show".to_string(),
+        title: options.title.clone(),
+        parent: options.parent.as_ref().map(|x| x.describe()),
+        color: options.color.map(|x| x.to_hex(true)),
+        alpha: Some(options.alpha),
+        ..Default::default()
+    })?;
+    match answer {
+        MockAnswer::Color(color) => Ok(Some(color)),
+        MockAnswer::Cancel => Ok(None),
+        _ => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            "Mock answer for a color dialog's show() is not a color",
+        )),
+    }
+}
+
+pub(crate) fn font_dialog(options: &FontOptions) -> RfdResult<Option<Font>> {
+    let answer = next_answer(MockRequest {
+        method: "show".to_string(),
+        title: options.title.clone(),
+        parent: options.parent.as_ref().map(|x| x.describe()),
+        family: options.family.clone(),
+        style: options.style,
+        weight: options.weight,
+        size: options.size,
+        ..Default::default()
+    })?;
+    match answer {
+        MockAnswer::Font(font) => Ok(Some(font)),
+        MockAnswer::Cancel => Ok(None),
+        _ => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            "Mock answer for a font dialog's show() is not a font",
+        )),
+    }
+}
+
+pub(crate) fn notification(options: &NotificationOptions) -> RfdResult<Option<String>> {
+    let answer = next_answer(MockRequest {
+        method: "show".to_string(),
+        title: options.title.clone(),
+        description: options.body.clone(),
+        level: options.urgency,
+        timeout: options.timeout,
+        icon: options.icon.clone(),
+        actions: Some(options.actions.clone()),
+        ..Default::default()
+    })?;
+    match answer {
+        MockAnswer::Button(Either::B(action)) => Ok(Some(action)),
+        MockAnswer::Cancel | MockAnswer::Timeout => Ok(None),
+        _ => Err(RfdError::new(
+            ErrorCode::InvalidArg,
+            "Mock answer for a notification's show() is not an action",
+        )),
+    }
+}
+
+/// Replaces the native dialogs with a headless mock backend. After this, every
+/// `FileDialog`, `AsyncFileDialog`, `MessageDialog` and `AsyncMessageDialog`
+/// will answer with the next queued answer instead of opening a window. This
+/// also clears any previously queued answers and recorded requests. Use this in
+/// automated tests that run without a display.
+///
+/// Example:
+///
+/// ```js
+/// mock.install();
+/// mock.pushPaths(['/home/username/Pictures/image.png']);
+/// const path = new FileDialog().pickFile();
+/// console.log(path);
+/// //=> '/home/username/Pictures/image.png'
+/// ```
+#[napi(namespace = "mock")]
+pub fn install() {
+    reset();
+    backend::set(Some(Backend::Mock));
+}
+
+/// Switches back to the default backend (the native OS dialogs unless the
+/// `RFD_BACKEND` environment variable says otherwise). Queued answers and
+/// recorded requests are kept around until the next `mock.reset()` or
+/// `mock.install()`.
+///
+/// Example:
+///
+/// ```js
+/// mock.uninstall();
+/// ```
+#[napi(namespace = "mock")]
+pub fn uninstall() {
+    backend::set(None);
+}
+
+/// Clears all queued answers and recorded requests without changing which
+/// backend is in use.
+///
+/// Example:
+///
+/// ```js
+/// mock.reset();
+/// console.log(mock.requests());
+/// //=> []
+/// ```
+#[napi(namespace = "mock")]
+pub fn reset() {
+    let mut state = STATE.lock().unwrap();
+    state.answers.clear();
+    state.requests.clear();
+}
+
+/// Queues a list of paths as the answer to the next file dialog. Single-item
+/// methods like `.pickFile()` use the first path, while `.pickFiles()`,
+/// `.pickFolders()` and `.saveFiles()` use all of them. Paths can also be `Buffer` instances with
+/// raw bytes to simulate file names that aren't valid UTF-8.
+///
+/// Example:
+///
+/// ```js
+/// mock.pushPaths(['/tmp/a.txt', '/tmp/b.txt']);
+/// const paths = new FileDialog().pickFiles();
+/// console.log(paths);
+/// //=> ['/tmp/a.txt', '/tmp/b.txt']
+/// ```
+#[napi(namespace = "mock")]
+pub fn push_paths(paths: Vec<Either<String, Buffer>>) {
+    let paths = paths.into_iter().map(path_from_js).collect();
+    STATE
+        .lock()
+        .unwrap()
+        .answers
+        .push_back(MockAnswer::Paths(paths));
+}
+
+/// Queues a button result as the answer to the next message dialog. `true`
+/// means the affirmative button was clicked and `false` the negative one, which
+/// are turned into `'Ok'`, `'Yes'`, `'No'` or a custom label depending on the
+/// buttons of that dialog. A string is returned from `.show()` as-is, which is
+/// also how a notification is told which action was clicked.
+///
+/// Example:
+///
+/// ```js
+/// mock.pushButton(true);
+/// const answer = await new AsyncMessageDialog().setButtons('OkCancel').show();
+/// console.log(answer);
+/// //=> 'Ok'
+/// ```
+#[napi(namespace = "mock")]
+pub fn push_button(result: Either<bool, String>) {
+    STATE
+        .lock()
+        .unwrap()
+        .answers
+        .push_back(MockAnswer::Button(result));
+}
+
+/// Queues a cancellation as the answer to the next dialog. File, input, color
+/// and font dialogs will return `null` and message dialogs will return
+/// `'Cancel'`, just like when the user closes the window. Notifications
+/// resolve to `null` as if they were dismissed.
+///
+/// Example:
+///
+/// ```js
+/// mock.pushCancel();
+/// const fileHandle = await new AsyncFileDialog().pickFile();
+/// console.log(fileHandle);
+/// //=> null
+/// ```
+#[napi(namespace = "mock")]
+pub fn push_cancel() {
+    STATE.lock().unwrap().answers.push_back(MockAnswer::Cancel);
+}
+
+/// Queues the text that the next input dialog returns, as if the user typed it
+/// in and clicked OK.
+///
+/// Example:
+///
+/// ```js
+/// mock.pushText('Photos');
+/// const name = await new AsyncInputDialog().show();
+/// console.log(name);
+/// //=> 'Photos'
+/// ```
+#[napi(namespace = "mock")]
+pub fn push_text(text: String) {
+    STATE
+        .lock()
+        .unwrap()
+        .answers
+        .push_back(MockAnswer::Text(text));
+}
+
+/// Queues the color that the next color dialog returns, as a CSS hex color like
+/// `'#ff8800'`. Throws an `ERR_INVALID_ARG` error for anything else.
+///
+/// Example:
+///
+/// ```js
+/// mock.pushColor('#ff8800');
+/// const color = await new AsyncColorDialog().show();
+/// console.log(color.hex);
+/// //=> '#ff8800'
+/// ```
+#[napi(namespace = "mock")]
+pub fn push_color(hex: String) -> Result<(), ErrorCode> {
+    let color = Rgba::from_hex(&hex)?;
+    STATE
+        .lock()
+        .unwrap()
+        .answers
+        .push_back(MockAnswer::Color(color));
+    return Ok(());
+}
+
+/// Queues the font that the next font dialog returns. Throws an
+/// `ERR_INVALID_ARG` error if the weight or size is out of range.
+///
+/// Example:
+///
+/// ```js
+/// mock.pushFont({ family: 'Fira Code', style: 'normal', weight: 400, size: 12 });
+/// const font = await new AsyncFontDialog().show();
+/// console.log(font.family);
+/// //=> 'Fira Code'
+/// ```
+#[napi(namespace = "mock")]
+pub fn push_font(font: Font) -> Result<(), ErrorCode> {
+    check_weight(font.weight)?;
+    check_size(font.size)?;
+    STATE
+        .lock()
+        .unwrap()
+        .answers
+        .push_back(MockAnswer::Font(font));
+    return Ok(());
+}
+
+/// Queues a timeout as the answer to the next message dialog, as if nobody
+/// clicked a button before the `.setTimeout()` ran out. The dialog returns its
+/// default result then. There is no actual waiting involved. A dialog without a
+/// `.setTimeout()` can't time out, so it throws `ERR_INVALID_ARG` instead.
+///
+/// Example:
+///
+/// ```js
+/// mock.pushTimeout();
+/// const response = await new AsyncMessageDialog()
+///     .setTimeout(60_000, 'Yes')
+///     .showDetailed();
+/// console.log(response);
+/// //=> { result: 'Yes', timedOut: true }
+/// ```
+#[napi(namespace = "mock")]
+pub fn push_timeout() {
+    STATE.lock().unwrap().answers.push_back(MockAnswer::Timeout);
+}
+
+/// Returns every dialog that was requested since the last `mock.install()` or
+/// `mock.reset()`, oldest first.
+///
+/// Example:
+///
+/// ```js
+/// mock.pushCancel();
+/// new FileDialog().addFilter('Text', ['txt']).pickFile();
+/// console.log(mock.requests()[0].filters);
+/// //=> [{ name: 'Text', extensions: ['txt'] }]
+/// ```
+#[napi(namespace = "mock")]
+pub fn requests() -> Vec<MockRequest> {
+    return STATE.lock().unwrap().requests.clone();
+}
@@ -0,0 +1,197 @@
+use super::abort::*;
+use super::backend;
+use super::message_level::*;
+use napi::bindgen_prelude::*;
+
+/// A button on a notification. `id` is what `Notification.show()` resolves to
+/// when it's clicked, and `label` is the text that the user sees.
+#[napi(object)]
+#[derive(Clone)]
+pub struct NotificationAction {
+    pub id: String,
+    pub label: String,
+}
+
+/// The plain-data configuration of a notification. Used by the `Notification`
+/// builder.
+#[derive(Clone, Default)]
+pub(crate) struct NotificationOptions {
+    pub(crate) title: Option<String>,
+    pub(crate) body: Option<String>,
+    /// A path to an image file.
+    pub(crate) icon: Option<String>,
+    pub(crate) urgency: Option<MessageLevel>,
+    pub(crate) actions: Vec<NotificationAction>,
+    /// Milliseconds until the notification expires. `0` means never and `None`
+    /// leaves it up to the notification server.
+    pub(crate) timeout: Option<u32>,
+}
+
+/// The builder for a desktop notification. Unlike a `MessageDialog`, a
+/// notification doesn't block the rest of the desktop: it pops up in a corner
+/// of the screen and goes away on its own.
+///
+/// Notifications are sent to the `org.freedesktop.Notifications` service over
+/// the D-Bus session bus, so they're only available on Linux and the BSDs.
+/// Every backend but `mock` uses it, even `zenity` and `kdialog`.
+///
+/// Example:
+///
+/// ```js
+/// const action = await new Notification()
+///     .setTitle('Download finished')
+///     .setBody('cat.png was saved to your Downloads folder.')
+///     .addAction('open', 'Open')
+///     .show();
+/// console.log(action);
+/// //=> 'open'
+/// ```
+#[napi]
+#[repr(transparent)]
+pub struct Notification(pub(super) NotificationOptions);
+impl Default for Notification {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+#[napi]
+impl Notification {
+    #[napi(constructor)]
+    pub fn new() -> Self {
+        return Self(NotificationOptions::default());
+    }
+
+    /// Returns a copy of this notification with the same settings.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const done = new Notification().setTitle('Done');
+    /// await done.clone().setBody('cat.png').show();
+    /// ```
+    #[napi(js_name = "clone")]
+    pub fn clone_(&self) -> Self {
+        return Self(self.0.clone());
+    }
+
+    /// Sets the title of the notification. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// await new Notification().setTitle('Download finished').show();
+    /// ```
+    #[napi]
+    pub fn set_title(&mut self, this: This, title: String) -> This {
+        let x = &mut self.0;
+        x.title = Some(title);
+        return this;
+    }
+
+    /// Sets the text below the title. Most notification servers understand
+    /// a few HTML-like tags like `<b>` and `<i>` in it. Returns `this` for
+    /// chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// await new Notification().setBody('Saved <b>cat.png</b>').show();
+    /// ```
+    #[napi]
+    pub fn set_body(&mut self, this: This, body: String) -> This {
+        let x = &mut self.0;
+        x.body = Some(body);
+        return this;
+    }
+
+    /// Sets the path of an image that is shown next to the text. Returns
+    /// `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// await new Notification().setIcon('/tmp/cat.png').show();
+    /// ```
+    #[napi]
+    pub fn set_icon(&mut self, this: This, path: String) -> This {
+        let x = &mut self.0;
+        x.icon = Some(path);
+        return this;
+    }
+
+    /// Sets how urgent the notification is. `Info` and `Warning` are sent with
+    /// normal urgency and `Error` with critical urgency, which usually means
+    /// that it doesn't expire on its own. Without `setIcon()`, the level also
+    /// picks the `dialog-information`, `dialog-warning` or `dialog-error`
+    /// icon, which is what sets `Warning` apart from `Info`. Returns `this` for
+    /// chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// await new Notification().setUrgency('Error').show();
+    /// ```
+    #[napi]
+    pub fn set_urgency(&mut self, this: This, level: MessageLevel) -> This {
+        let x = &mut self.0;
+        x.urgency = Some(level);
+        return this;
+    }
+
+    /// Adds a button to the notification. When it's clicked, `show()` resolves
+    /// to its `id`. Returns `this` for chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const action = await new Notification()
+    ///     .addAction('retry', 'Try again')
+    ///     .addAction('ignore', 'Ignore')
+    ///     .show();
+    /// ```
+    #[napi]
+    pub fn add_action(&mut self, this: This, id: String, label: String) -> This {
+        let x = &mut self.0;
+        x.actions.push(NotificationAction { id, label });
+        return this;
+    }
+
+    /// Makes the notification expire after `ms` milliseconds, or never with
+    /// `0`. By default, the notification server decides. Returns `this` for
+    /// chaining.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// await new Notification().setTitle('Saved').setTimeout(5_000).show();
+    /// ```
+    #[napi]
+    pub fn set_timeout(&mut self, this: This, ms: u32) -> This {
+        let x = &mut self.0;
+        x.timeout = Some(ms);
+        return this;
+    }
+
+    /// Sends the notification and returns a promise that resolves to the `id`
+    /// of the action that was clicked, or `null` once the notification is
+    /// dismissed or expires. Aborting the signal closes the notification.
+    ///
+    /// Example:
+    ///
+    /// ```js
+    /// const action = await new Notification()
+    ///     .setTitle('New message')
+    ///     .addAction('reply', 'Reply')
+    ///     .show({ signal: AbortSignal.timeout(30_000) });
+    /// ```
+    #[napi(ts_return_type = "Promise<string | null>")]
+    pub async unsafe fn show(
+        &self,
+        options: Option<AbortOptions>,
+    ) -> AbortableResult<Option<String>> {
+        let x = self.0.clone();
+        let y = backend::async_notification(x, abort_token(&options)).await;
+        return AbortableResult(y, options);
+    }
+}
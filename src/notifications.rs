@@ -0,0 +1,149 @@
+use super::abort::*;
+use super::error::*;
+use super::message_level::*;
+use super::notification::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use zbus::export::futures_util::StreamExt;
+use zbus::zvariant::Value;
+use zbus::{Connection, Proxy};
+
+const DESTINATION: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+fn dbus_error(error: zbus::Error) -> RfdError {
+    return RfdError::new(
+        ErrorCode::BackendUnavailable,
+        format!("Notification server: {}", error),
+    );
+}
+
+/// Turns an icon path into the `file://` URI that the notification server
+/// wants, escaping everything but the unreserved characters.
+fn uri_from_path(path: &str) -> String {
+    let path = std::env::current_dir()
+        .map(|x| x.join(path))
+        .unwrap_or_else(|_| PathBuf::from(path));
+    let mut uri = "file://".to_string();
+    for byte in path.to_string_lossy().bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    return uri;
+}
+
+/// The `urgency` hint: 1 is normal and 2 is critical. Low urgency (0) isn't
+/// used since servers may hide those notifications without a popup.
+fn urgency(level: MessageLevel) -> u8 {
+    return match level {
+        MessageLevel::Info => 1,
+        MessageLevel::Warning => 1,
+        MessageLevel::Error => 2,
+    };
+}
+
+/// The icon from the freedesktop icon naming spec that is shown for `level`
+/// when there's no `setIcon()`. Info and Warning have the same urgency, so
+/// this is what tells them apart.
+fn level_icon(level: MessageLevel) -> &'static str {
+    return match level {
+        MessageLevel::Info => "dialog-information",
+        MessageLevel::Warning => "dialog-warning",
+        MessageLevel::Error => "dialog-error",
+    };
+}
+
+/// Sends a notification through the `org.freedesktop.Notifications` D-Bus
+/// interface and waits for it to go away. Returns the key of the action that
+/// was invoked, or `None` if the notification expired or was dismissed.
+/// Aborting `abort` closes it through `CloseNotification()`.
+pub(crate) async fn notify(
+    options: &NotificationOptions,
+    abort: &AbortToken,
+) -> RfdResult<Option<String>> {
+    let connection = Connection::session().await.map_err(dbus_error)?;
+    let notifications = Proxy::new(&connection, DESTINATION, PATH, INTERFACE)
+        .await
+        .map_err(dbus_error)?;
+
+    // Subscribe before sending so that a quick answer can't be missed. The
+    // signals are broadcast for every notification, so they're matched by id.
+    let mut signals = notifications
+        .receive_all_signals()
+        .await
+        .map_err(dbus_error)?;
+
+    let actions: Vec<&str> = options
+        .actions
+        .iter()
+        .flat_map(|x| [x.id.as_str(), x.label.as_str()])
+        .collect();
+    let mut hints: HashMap<&str, Value> = HashMap::new();
+    if let Some(level) = options.urgency {
+        hints.insert("urgency", urgency(level).into());
+    }
+    let icon = options
+        .icon
+        .as_ref()
+        .map(|x| uri_from_path(x))
+        .or_else(|| options.urgency.map(|x| level_icon(x).to_string()))
+        .unwrap_or_default();
+    let timeout = match options.timeout {
+        Some(ms) => i32::try_from(ms).unwrap_or(i32::MAX),
+        None => -1,
+    };
+    let id: u32 = notifications
+        .call(
+            "Notify",
+            &(
+                "",
+                0u32,
+                icon.as_str(),
+                options.title.as_deref().unwrap_or_default(),
+                options.body.as_deref().unwrap_or_default(),
+                actions,
+                hints,
+                timeout,
+            ),
+        )
+        .await
+        .map_err(dbus_error)?;
+
+    let answer = abort
+        .race(async {
+            while let Some(message) = signals.next().await {
+                let member = message.member();
+                match member.as_ref().map(|x| x.as_str()) {
+                    Some("ActionInvoked") => {
+                        let (x, action): (u32, String) = message.body().map_err(dbus_error)?;
+                        if x == id {
+                            return Ok(Some(action));
+                        }
+                    }
+                    Some("NotificationClosed") => {
+                        let (x, _reason): (u32, u32) = message.body().map_err(dbus_error)?;
+                        if x == id {
+                            return Ok(None);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            return Err(RfdError::new(
+                ErrorCode::BackendUnavailable,
+                "Notification server closed the connection",
+            ));
+        })
+        .await;
+    if let Err(error) = answer {
+        // The notification may already be gone, in which case there is
+        // nothing to close.
+        let _: zbus::Result<()> = notifications.call("CloseNotification", &(id,)).await;
+        return Err(error);
+    }
+    return answer.unwrap();
+}
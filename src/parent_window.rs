@@ -0,0 +1,110 @@
+use super::error::*;
+use napi::bindgen_prelude::*;
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+
+/// A native window that a dialog should be modal for. This is plain data so it
+/// can be stored in the dialog options and sent to a worker thread.
+///
+/// On the JavaScript side, numbers and bigints are the native window handle of
+/// the current platform: an X11 window id on Linux and the BSDs, an `HWND` on
+/// Windows and an `NSWindow*` on macOS. Strings are Wayland surface handles
+/// exported with `xdg-foreign`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ParentWindow {
+    X11(u64),
+    Wayland(String),
+    Win32(u64),
+    AppKit(u64),
+}
+impl ParentWindow {
+    pub(crate) fn from_js(handle: Either3<i64, BigInt, String>) -> RfdResult<Self> {
+        let raw = match handle {
+            Either3::A(number) => {
+                if number <= 0 {
+                    return Err(RfdError::new(
+                        ErrorCode::InvalidArg,
+                        "Parent window handle must be a positive number",
+                    ));
+                }
+                number as u64
+            }
+            Either3::B(bigint) => {
+                let (signed, raw, lossless) = bigint.get_u64();
+                if signed || !lossless || raw == 0 {
+                    return Err(RfdError::new(
+                        ErrorCode::InvalidArg,
+                        "Parent window handle must be a positive 64-bit bigint",
+                    ));
+                }
+                raw
+            }
+            Either3::C(string) => {
+                if cfg!(any(target_os = "windows", target_os = "macos")) || string.is_empty() {
+                    return Err(RfdError::new(
+                        ErrorCode::InvalidArg,
+                        "Wayland parent window handles are only supported on Linux and the BSDs",
+                    ));
+                }
+                return Ok(ParentWindow::Wayland(string));
+            }
+        };
+        if cfg!(target_os = "windows") {
+            return Ok(ParentWindow::Win32(raw));
+        }
+        if cfg!(target_os = "macos") {
+            return Ok(ParentWindow::AppKit(raw));
+        }
+        return Ok(ParentWindow::X11(raw));
+    }
+
+    /// A short description like `'x11:0x3a00007'` for the mock backend.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            ParentWindow::X11(x) => format!("x11:{:#x}", x),
+            ParentWindow::Wayland(x) => format!("wayland:{}", x),
+            ParentWindow::Win32(x) => format!("win32:{:#x}", x),
+            ParentWindow::AppKit(x) => format!("appkit:{:#x}", x),
+        }
+    }
+
+    /// The `parent_window` string of the XDG desktop portal, like
+    /// `'x11:3a00007'`. The portal doesn't know about other platforms.
+    pub(crate) fn portal_handle(&self) -> String {
+        match self {
+            ParentWindow::X11(x) => format!("x11:{:x}", x),
+            ParentWindow::Wayland(x) => format!("wayland:{}", x),
+            _ => String::new(),
+        }
+    }
+
+    /// Whether `rfd` can use this handle. Wayland handles can only be passed
+    /// to the XDG desktop portal, not to a native toolkit.
+    pub(crate) fn is_raw(&self) -> bool {
+        return !matches!(self, ParentWindow::Wayland(_));
+    }
+}
+unsafe impl HasRawWindowHandle for ParentWindow {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        match self {
+            ParentWindow::X11(x) => {
+                let mut handle = raw_window_handle::XlibWindowHandle::empty();
+                handle.window = *x as _;
+                RawWindowHandle::Xlib(handle)
+            }
+            ParentWindow::Wayland(_) => {
+                let handle = raw_window_handle::WaylandWindowHandle::empty();
+                RawWindowHandle::Wayland(handle)
+            }
+            ParentWindow::Win32(x) => {
+                let mut handle = raw_window_handle::Win32WindowHandle::empty();
+                handle.hwnd = *x as _;
+                RawWindowHandle::Win32(handle)
+            }
+            ParentWindow::AppKit(x) => {
+                let mut handle = raw_window_handle::AppKitWindowHandle::empty();
+                handle.ns_window = *x as _;
+                RawWindowHandle::AppKit(handle)
+            }
+        }
+    }
+}
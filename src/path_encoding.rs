@@ -0,0 +1,67 @@
+use super::error::*;
+use napi::bindgen_prelude::*;
+use std::path::{Path, PathBuf};
+
+/// How the synchronous `FileDialog` returns the paths that were picked. The
+/// default `Utf8` returns strings and throws `ERR_NON_UTF8_PATH` if a path
+/// can't be represented as one. `Buffer` always returns the raw bytes of the
+/// path as a `Buffer`, just like `{ encoding: 'buffer' }` does for `node:fs`
+/// functions. These buffers can be passed to any `node:fs` function as-is.
+///
+/// Example:
+///
+/// ```js
+/// const path = new FileDialog().setPathEncoding('Buffer').pickFile();
+/// const bytes = await readFile(path);
+/// ```
+#[napi(string_enum)]
+pub enum PathEncoding {
+    Utf8,
+    Buffer,
+}
+impl PathEncoding {
+    pub(crate) fn encode(&self, path_buf: PathBuf) -> RfdResult<Either<String, Buffer>> {
+        match self {
+            PathEncoding::Buffer => Ok(Either::B(path_to_buffer(&path_buf))),
+            PathEncoding::Utf8 => {
+                let path = path_buf.to_str();
+                if path.is_none() {
+                    return Err(RfdError::non_utf8_path(&path_buf));
+                }
+                let path = path.unwrap();
+                let path = path.to_string();
+                return Ok(Either::A(path));
+            }
+        }
+    }
+}
+
+/// Returns the raw bytes of the path. On Windows, where paths are UTF-16, this
+/// is the (possibly lossy) UTF-8 version since that's what Node.js expects for
+/// `Buffer` paths there.
+pub(crate) fn path_to_buffer(path: &Path) -> Buffer {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        return path.as_os_str().as_bytes().to_vec().into();
+    }
+    #[cfg(not(unix))]
+    {
+        return path.to_string_lossy().as_bytes().to_vec().into();
+    }
+}
+
+/// The opposite of `path_to_buffer()`. Accepts either a normal string or a
+/// `Buffer` with the raw bytes of the path.
+pub(crate) fn path_from_js(path: Either<String, Buffer>) -> PathBuf {
+    match path {
+        Either::A(path) => PathBuf::from(path),
+        #[cfg(unix)]
+        Either::B(bytes) => {
+            use std::os::unix::ffi::OsStrExt;
+            PathBuf::from(std::ffi::OsStr::from_bytes(&bytes))
+        }
+        #[cfg(not(unix))]
+        Either::B(bytes) => PathBuf::from(String::from_utf8_lossy(&bytes).into_owned()),
+    }
+}
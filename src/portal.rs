@@ -0,0 +1,244 @@
+use super::abort::*;
+use super::error::*;
+use super::file_dialog::*;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use zbus::export::futures_util::StreamExt;
+use zbus::zvariant::{OwnedObjectPath, Value};
+use zbus::{Connection, Proxy};
+
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const PATH: &str = "/org/freedesktop/portal/desktop";
+
+/// Makes the `handle_token` of every request unique within this process.
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(0);
+
+fn dbus_error(error: zbus::Error) -> RfdError {
+    return RfdError::new(
+        ErrorCode::BackendUnavailable,
+        format!("XDG desktop portal: {}", error),
+    );
+}
+
+/// The portal wants folders as null-terminated byte strings (`ay`) so that they
+/// don't have to be valid UTF-8.
+fn path_bytes(path: &Path) -> Vec<u8> {
+    let mut bytes = path.as_os_str().as_bytes().to_vec();
+    bytes.push(0);
+    return bytes;
+}
+
+/// Turns the `file://` URIs returned by the portal back into paths. Inside of
+/// a sandbox these point into the document portal (`/run/user/…/doc/…`).
+fn path_from_uri(uri: &str) -> RfdResult<PathBuf> {
+    let rest = uri.strip_prefix("file://");
+    if rest.is_none() {
+        return Err(RfdError::new(
+            ErrorCode::BackendUnavailable,
+            format!("XDG desktop portal returned a non-file URI: {}", uri),
+        ));
+    }
+    let rest = rest.unwrap();
+    // Skip the host (usually empty or "localhost").
+    let rest = &rest[rest.find('/').unwrap_or(rest.len())..];
+    let mut bytes = Vec::with_capacity(rest.len());
+    let mut iter = rest.bytes();
+    while let Some(byte) = iter.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+        let hex: Vec<u8> = iter.by_ref().take(2).collect();
+        let decoded = std::str::from_utf8(&hex)
+            .ok()
+            .and_then(|x| u8::from_str_radix(x, 16).ok());
+        if decoded.is_none() {
+            return Err(RfdError::new(
+                ErrorCode::BackendUnavailable,
+                format!("XDG desktop portal returned a malformed URI: {}", uri),
+            ));
+        }
+        bytes.push(decoded.unwrap());
+    }
+    return Ok(PathBuf::from(OsStr::from_bytes(&bytes)));
+}
+
+fn filters(options: &FileOptions) -> Vec<(String, Vec<(u32, String)>)> {
+    return options
+        .filters
+        .iter()
+        .map(|filter| {
+            // 0 means that the pattern is a glob, 1 would be a MIME type.
+            let patterns = filter
+                .extensions
+                .iter()
+                .map(|x| match x.as_str() {
+                    "*" => (0, "*".to_string()),
+                    _ => (0, format!("*.{}", x)),
+                })
+                .collect();
+            (filter.name.clone(), patterns)
+        })
+        .collect();
+}
+
+/// Picks the portal method for a `FileMethod` and fills in its `a{sv}`
+/// options.
+fn method_options<'a>(
+    method: FileMethod,
+    options: &'a FileOptions,
+    token: &'a str,
+) -> (&'static str, HashMap<&'static str, Value<'a>>) {
+    let mut x: HashMap<&'static str, Value<'a>> = HashMap::new();
+    x.insert("handle_token", token.into());
+    x.insert("modal", options.parent.is_some().into());
+    let name = match method {
+        FileMethod::PickFile => "OpenFile",
+        FileMethod::PickFiles => {
+            x.insert("multiple", true.into());
+            "OpenFile"
+        }
+        FileMethod::PickFolder => {
+            x.insert("directory", true.into());
+            "OpenFile"
+        }
+        FileMethod::PickFolders => {
+            x.insert("directory", true.into());
+            x.insert("multiple", true.into());
+            "OpenFile"
+        }
+        FileMethod::SaveFile => {
+            if let Some(file_name) = &options.file_name {
+                x.insert("current_name", file_name.as_str().into());
+            }
+            "SaveFile"
+        }
+        FileMethod::SaveFiles => {
+            let files: Vec<Vec<u8>> = options
+                .file_names
+                .iter()
+                .map(|x| path_bytes(Path::new(x)))
+                .collect();
+            x.insert("files", files.into());
+            "SaveFiles"
+        }
+    };
+    if !options.filters.is_empty() && name != "SaveFiles" {
+        x.insert("filters", filters(options).into());
+    }
+    if let Some(directory) = &options.directory {
+        x.insert("current_folder", path_bytes(Path::new(directory)).into());
+    }
+    return (name, x);
+}
+
+/// Shows a file dialog through the `org.freedesktop.portal.FileChooser` D-Bus
+/// interface. This works from inside of Flatpak and other sandboxes, where the
+/// picked files are exported through the document portal. The session bus is
+/// found through `$DBUS_SESSION_BUS_ADDRESS` like any other D-Bus client.
+/// Aborting `abort` closes the dialog through `Request.Close()`.
+pub(crate) async fn file_dialog(
+    method: FileMethod,
+    options: &FileOptions,
+    abort: &AbortToken,
+) -> RfdResult<Option<Vec<PathBuf>>> {
+    let connection = Connection::session().await.map_err(dbus_error)?;
+    let file_chooser = Proxy::new(
+        &connection,
+        DESTINATION,
+        PATH,
+        "org.freedesktop.portal.FileChooser",
+    )
+    .await
+    .map_err(dbus_error)?;
+
+    // Subscribe to the response before making the call so that it can't be
+    // missed. The request path is derived from our unique name and the token.
+    let token = format!("rfd_js_{}", NEXT_TOKEN.fetch_add(1, Ordering::Relaxed));
+    let sender = connection
+        .unique_name()
+        .map(|x| x.trim_start_matches(':').replace('.', "_"))
+        .unwrap_or_default();
+    let request_path = format!("{}/request/{}/{}", PATH, sender, token);
+    let mut request = Proxy::new(
+        &connection,
+        DESTINATION,
+        request_path.as_str(),
+        "org.freedesktop.portal.Request",
+    )
+    .await
+    .map_err(dbus_error)?;
+    let mut responses = request
+        .receive_signal("Response")
+        .await
+        .map_err(dbus_error)?;
+
+    let (name, x) = method_options(method, options, &token);
+    let parent = options
+        .parent
+        .as_ref()
+        .map(|x| x.portal_handle())
+        .unwrap_or_default();
+    let title = options.title.clone().unwrap_or_default();
+    let handle: OwnedObjectPath = file_chooser
+        .call(name, &(parent, title, x))
+        .await
+        .map_err(dbus_error)?;
+    if handle.as_str() != request_path {
+        // Portals older than version 0.9 ignore the token.
+        request = Proxy::new(
+            &connection,
+            DESTINATION,
+            handle.into_inner(),
+            "org.freedesktop.portal.Request",
+        )
+        .await
+        .map_err(dbus_error)?;
+        responses = request
+            .receive_signal("Response")
+            .await
+            .map_err(dbus_error)?;
+    }
+
+    let message = abort.race(responses.next()).await;
+    if let Err(error) = message {
+        // The dialog may already be gone, in which case there is nothing to
+        // close.
+        let _: zbus::Result<()> = request.call("Close", &()).await;
+        return Err(error);
+    }
+    let message = message.unwrap();
+    if message.is_none() {
+        return Err(RfdError::new(
+            ErrorCode::BackendUnavailable,
+            "XDG desktop portal closed the connection",
+        ));
+    }
+    let message = message.unwrap();
+    let (response, results): (u32, HashMap<String, Value>) = message.body().map_err(dbus_error)?;
+    match response {
+        0 => {}
+        1 => return Ok(None),
+        _ => {
+            return Err(RfdError::new(
+                ErrorCode::BackendUnavailable,
+                "XDG desktop portal request failed",
+            ));
+        }
+    }
+    let mut path_bufs = Vec::new();
+    if let Some(Value::Array(uris)) = results.get("uris") {
+        for uri in uris.iter() {
+            if let Value::Str(uri) = uri {
+                path_bufs.push(path_from_uri(uri.as_str())?);
+            }
+        }
+    }
+    if path_bufs.is_empty() {
+        return Ok(None);
+    }
+    return Ok(Some(path_bufs));
+}
@@ -0,0 +1,616 @@
+use super::abort::*;
+use super::color_dialog::*;
+use super::error::*;
+use super::file_dialog::*;
+use super::input_dialog::*;
+use super::message_buttons::*;
+use super::message_dialog::*;
+use super::message_level::*;
+use super::parent_window::*;
+use super::path_encoding::*;
+use napi::bindgen_prelude::*;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a running dialog program is checked for having exited or for its
+/// `AbortSignal` having been aborted.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A dialog program that can be run as a subprocess instead of linking to a
+/// GUI toolkit. Both are found through `$PATH`.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Tool {
+    /// GNOME's `zenity`.
+    Zenity,
+    /// KDE's `kdialog`.
+    Kdialog,
+}
+impl Tool {
+    fn program(&self) -> &'static str {
+        match self {
+            Tool::Zenity => "zenity",
+            Tool::Kdialog => "kdialog",
+        }
+    }
+}
+
+/// The exit code and stdout (without the trailing newline) of a finished
+/// dialog program.
+struct Output {
+    status: i32,
+    stdout: Vec<u8>,
+}
+
+/// Runs the dialog program to completion. The program is killed when `abort`
+/// is aborted, which closes the dialog.
+fn run(tool: Tool, args: &[String], abort: &AbortToken) -> RfdResult<Output> {
+    let output = run_until(tool, args, Vec::new(), abort, None)?;
+    return Ok(output.unwrap());
+}
+
+/// Like `run()`, but also writes `input` to the program's stdin and kills the
+/// program once `timeout` has passed, returning `None` in that case.
+fn run_until(
+    tool: Tool,
+    args: &[String],
+    input: Vec<u8>,
+    abort: &AbortToken,
+    timeout: Option<Duration>,
+) -> RfdResult<Option<Output>> {
+    let deadline = timeout.map(|x| Instant::now() + x);
+    let child = Command::new(tool.program())
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    if let Err(error) = child {
+        if error.kind() == io::ErrorKind::NotFound {
+            return Err(RfdError::new(
+                ErrorCode::BackendUnavailable,
+                format!("{} is not installed or not on $PATH", tool.program()),
+            ));
+        }
+        return Err(RfdError::io(error, "spawn", Path::new(tool.program())));
+    }
+    let mut child = child.unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    if !input.is_empty() {
+        // The program may not read all of it, so a failed write is fine.
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    } else {
+        drop(stdin);
+    }
+    // Read stdout on another thread so that a long list of paths can't fill
+    // up the pipe while we're waiting for the program to exit.
+    let mut pipe = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || {
+        let mut stdout = Vec::new();
+        return pipe.read_to_end(&mut stdout).map(|_| stdout);
+    });
+    let status = loop {
+        if abort.is_aborted() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(RfdError::aborted());
+        }
+        if deadline.is_some_and(|x| Instant::now() >= x) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(error) => return Err(RfdError::io(error, "wait", Path::new(tool.program()))),
+        }
+    };
+    let stdout = reader.join().unwrap();
+    if let Err(error) = stdout {
+        return Err(RfdError::io(error, "read", Path::new(tool.program())));
+    }
+    let status = status.code();
+    if status.is_none() {
+        return Err(RfdError::new(
+            ErrorCode::BackendUnavailable,
+            format!("{} was killed by a signal", tool.program()),
+        ));
+    }
+    let mut stdout = stdout.unwrap();
+    if stdout.last() == Some(&b'\n') {
+        stdout.pop();
+    }
+    return Ok(Some(Output {
+        status: status.unwrap(),
+        stdout,
+    }));
+}
+
+fn unexpected_status(tool: Tool, status: i32) -> RfdError {
+    return RfdError::new(
+        ErrorCode::BackendUnavailable,
+        format!("{} exited with status {}", tool.program(), status),
+    );
+}
+
+/// The path that the dialog should start at: the directory, the file name or
+/// both joined together.
+fn start_path(options: &FileOptions) -> Option<String> {
+    let mut path = PathBuf::new();
+    if let Some(directory) = &options.directory {
+        path.push(directory);
+    }
+    if let Some(file_name) = &options.file_name {
+        path.push(file_name);
+    } else if options.directory.is_some() {
+        // A trailing slash tells zenity to open inside of the directory.
+        path.push("");
+    }
+    if path.as_os_str().is_empty() {
+        return None;
+    }
+    return Some(path.to_string_lossy().into_owned());
+}
+
+fn x11_window(options: Option<&ParentWindow>) -> Option<String> {
+    match options {
+        Some(ParentWindow::X11(id)) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// The glob patterns for a filter, separated by spaces. The `"*"` extension
+/// matches every file, not just the ones that have an extension.
+fn patterns(filter: &FileFilter) -> String {
+    let patterns: Vec<String> = filter
+        .extensions
+        .iter()
+        .map(|x| match x.as_str() {
+            "*" => "*".to_string(),
+            _ => format!("*.{}", x),
+        })
+        .collect();
+    return patterns.join(" ");
+}
+
+fn zenity_file_args(method: FileMethod, options: &FileOptions) -> Vec<String> {
+    let mut args = vec!["--file-selection".to_string()];
+    match method {
+        FileMethod::PickFile => {}
+        FileMethod::PickFiles => args.push("--multiple".to_string()),
+        // The backend joins the file names to the folder for `SaveFiles`.
+        FileMethod::PickFolder | FileMethod::SaveFiles => args.push("--directory".to_string()),
+        FileMethod::PickFolders => {
+            args.push("--directory".to_string());
+            args.push("--multiple".to_string());
+        }
+        FileMethod::SaveFile => {
+            args.push("--save".to_string());
+            args.push("--confirm-overwrite".to_string());
+        }
+    }
+    args.push("--separator=\n".to_string());
+    if let Some(title) = &options.title {
+        args.push(format!("--title={}", title));
+    }
+    if let Some(path) = start_path(options) {
+        args.push(format!("--filename={}", path));
+    }
+    for filter in &options.filters {
+        args.push(format!(
+            "--file-filter={} | {}",
+            filter.name,
+            patterns(filter)
+        ));
+    }
+    if let Some(window) = x11_window(options.parent.as_ref()) {
+        args.push(format!("--attach={}", window));
+    }
+    return args;
+}
+
+fn kdialog_file_args(method: FileMethod, options: &FileOptions) -> RfdResult<Vec<String>> {
+    let mut args = Vec::new();
+    if let Some(title) = &options.title {
+        args.push("--title".to_string());
+        args.push(title.clone());
+    }
+    if let Some(window) = x11_window(options.parent.as_ref()) {
+        args.push("--attach".to_string());
+        args.push(window);
+    }
+    match method {
+        FileMethod::PickFile => args.push("--getopenfilename".to_string()),
+        FileMethod::PickFiles => {
+            args.push("--getopenfilename".to_string());
+            args.push("--multiple".to_string());
+            args.push("--separate-output".to_string());
+        }
+        FileMethod::PickFolder | FileMethod::SaveFiles => {
+            args.push("--getexistingdirectory".to_string())
+        }
+        FileMethod::PickFolders => {
+            return Err(RfdError::new(
+                ErrorCode::BackendUnavailable,
+                "kdialog can't pick multiple folders",
+            ));
+        }
+        FileMethod::SaveFile => args.push("--getsavefilename".to_string()),
+    }
+    args.push(start_path(options).unwrap_or_else(|| ".".to_string()));
+    if !options.filters.is_empty()
+        && !matches!(method, FileMethod::PickFolder | FileMethod::SaveFiles)
+    {
+        let filters: Vec<String> = options
+            .filters
+            .iter()
+            .map(|filter| format!("{} ({})", filter.name, patterns(filter)))
+            .collect();
+        args.push(filters.join("\n"));
+    }
+    return Ok(args);
+}
+
+/// Shows a file dialog with `zenity` or `kdialog`. Both print one path per
+/// line and exit with status 1 when the user cancels.
+pub(crate) fn file_dialog(
+    tool: Tool,
+    method: FileMethod,
+    options: &FileOptions,
+    abort: &AbortToken,
+) -> RfdResult<Option<Vec<PathBuf>>> {
+    let args = match tool {
+        Tool::Zenity => zenity_file_args(method, options),
+        Tool::Kdialog => kdialog_file_args(method, options)?,
+    };
+    let output = run(tool, &args, abort)?;
+    match output.status {
+        0 => {}
+        1 => return Ok(None),
+        status => return Err(unexpected_status(tool, status)),
+    }
+    if output.stdout.is_empty() {
+        return Ok(None);
+    }
+    let path_bufs = output
+        .stdout
+        .split(|x| *x == b'\n')
+        .map(|x| path_from_js(Either::B(x.to_vec().into())))
+        .collect();
+    return Ok(Some(path_bufs));
+}
+
+fn zenity_message_args(options: &MessageOptions) -> Vec<String> {
+    let buttons = options.buttons.unwrap_or(MessageButtons::Ok);
+    let labels = &options.labels;
+    let mut args = Vec::new();
+    match buttons {
+        MessageButtons::Ok | MessageButtons::OkCustom => {
+            let kind = match options.level.unwrap_or(MessageLevel::Info) {
+                MessageLevel::Info => "--info",
+                MessageLevel::Warning => "--warning",
+                MessageLevel::Error => "--error",
+            };
+            args.push(kind.to_string());
+            if matches!(buttons, MessageButtons::OkCustom) {
+                args.push(format!("--ok-label={}", labels[0]));
+            }
+        }
+        MessageButtons::OkCancel => {
+            args.push("--question".to_string());
+            args.push("--ok-label=OK".to_string());
+            args.push("--cancel-label=Cancel".to_string());
+        }
+        MessageButtons::YesNo => args.push("--question".to_string()),
+        // zenity exits with 1 for both the cancel button and Esc, so "No" is
+        // the extra button and pressing Esc cancels the dialog.
+        MessageButtons::YesNoCancel => {
+            args.push("--question".to_string());
+            args.push("--ok-label=Yes".to_string());
+            args.push("--cancel-label=Cancel".to_string());
+            args.push("--extra-button=No".to_string());
+        }
+        MessageButtons::OkCancelCustom => {
+            args.push("--question".to_string());
+            args.push(format!("--ok-label={}", labels[0]));
+            args.push(format!("--cancel-label={}", labels[1]));
+        }
+        MessageButtons::YesNoCancelCustom => {
+            args.push("--question".to_string());
+            args.push(format!("--ok-label={}", labels[0]));
+            args.push(format!("--cancel-label={}", labels[2]));
+            args.push(format!("--extra-button={}", labels[1]));
+        }
+    }
+    if let Some(title) = &options.title {
+        args.push(format!("--title={}", title));
+    }
+    args.push(format!(
+        "--text={}",
+        options.description.clone().unwrap_or_default()
+    ));
+    if let Some(window) = x11_window(options.parent.as_ref()) {
+        args.push(format!("--attach={}", window));
+    }
+    return args;
+}
+
+fn kdialog_message_args(options: &MessageOptions) -> Vec<String> {
+    let buttons = options.buttons.unwrap_or(MessageButtons::Ok);
+    let level = options.level.unwrap_or(MessageLevel::Info);
+    let labels = &options.labels;
+    let mut args = Vec::new();
+    if let Some(title) = &options.title {
+        args.push("--title".to_string());
+        args.push(title.clone());
+    }
+    if let Some(window) = x11_window(options.parent.as_ref()) {
+        args.push("--attach".to_string());
+        args.push(window);
+    }
+    let kind = match (buttons, level) {
+        (MessageButtons::Ok | MessageButtons::OkCustom, MessageLevel::Info) => "--msgbox",
+        (MessageButtons::Ok | MessageButtons::OkCustom, MessageLevel::Warning) => "--sorry",
+        (MessageButtons::Ok | MessageButtons::OkCustom, MessageLevel::Error) => "--error",
+        (MessageButtons::OkCancel | MessageButtons::OkCancelCustom, MessageLevel::Info) => {
+            "--continuecancel"
+        }
+        (MessageButtons::OkCancel | MessageButtons::OkCancelCustom, _) => "--warningcontinuecancel",
+        (MessageButtons::YesNo, MessageLevel::Info) => "--yesno",
+        (MessageButtons::YesNo, _) => "--warningyesno",
+        (MessageButtons::YesNoCancel | MessageButtons::YesNoCancelCustom, MessageLevel::Info) => {
+            "--yesnocancel"
+        }
+        (MessageButtons::YesNoCancel | MessageButtons::YesNoCancelCustom, _) => {
+            "--warningyesnocancel"
+        }
+    };
+    args.push(kind.to_string());
+    args.push(options.description.clone().unwrap_or_default());
+    match buttons {
+        MessageButtons::OkCustom => {
+            args.push("--ok-label".to_string());
+            args.push(labels[0].clone());
+        }
+        MessageButtons::OkCancel => {
+            args.push("--continue-label".to_string());
+            args.push("OK".to_string());
+        }
+        MessageButtons::OkCancelCustom => {
+            args.push("--continue-label".to_string());
+            args.push(labels[0].clone());
+            args.push("--cancel-label".to_string());
+            args.push(labels[1].clone());
+        }
+        MessageButtons::YesNoCancelCustom => {
+            args.push("--yes-label".to_string());
+            args.push(labels[0].clone());
+            args.push("--no-label".to_string());
+            args.push(labels[1].clone());
+            args.push("--cancel-label".to_string());
+            args.push(labels[2].clone());
+        }
+        _ => {}
+    }
+    return args;
+}
+
+/// Shows a message box with `zenity` or `kdialog` and turns the exit code (and
+/// for zenity's extra button, stdout) into the same result as the native
+/// backend. Returns `None` if nobody answered before the timeout.
+pub(crate) fn message_dialog(
+    tool: Tool,
+    options: &MessageOptions,
+    abort: &AbortToken,
+) -> RfdResult<Option<String>> {
+    let buttons = options.buttons.unwrap_or(MessageButtons::Ok);
+    let labels = &options.labels;
+    let args = match tool {
+        Tool::Zenity => zenity_message_args(options),
+        Tool::Kdialog => kdialog_message_args(options),
+    };
+    let timeout = options
+        .timeout
+        .as_ref()
+        .map(|x| Duration::from_millis(x.0.into()));
+    let output = run_until(tool, &args, Vec::new(), abort, timeout)?;
+    if output.is_none() {
+        return Ok(None);
+    }
+    let output = output.unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let result = match (tool, output.status) {
+        // zenity prints the label of the extra button and exits with 1.
+        (Tool::Zenity, 1) if !stdout.is_empty() => stdout,
+        (_, 0) => buttons.button_result(labels, true),
+        (Tool::Zenity, 1) => match buttons {
+            MessageButtons::YesNoCancel => "Cancel".to_string(),
+            MessageButtons::YesNoCancelCustom => labels[2].clone(),
+            _ => buttons.button_result(labels, false),
+        },
+        (Tool::Kdialog, 1) => match buttons {
+            MessageButtons::YesNo | MessageButtons::YesNoCancel => "No".to_string(),
+            MessageButtons::YesNoCancelCustom => labels[1].clone(),
+            _ => "Cancel".to_string(),
+        },
+        (Tool::Kdialog, 2) => match buttons {
+            MessageButtons::OkCancelCustom => labels[1].clone(),
+            MessageButtons::YesNoCancelCustom => labels[2].clone(),
+            _ => "Cancel".to_string(),
+        },
+        (_, status) => return Err(unexpected_status(tool, status)),
+    };
+    return Ok(Some(result));
+}
+
+fn zenity_input_args(options: &InputOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    if options.multiline && !options.password {
+        // The default value is written to stdin, see `input_dialog()`.
+        args.push("--text-info".to_string());
+        args.push("--editable".to_string());
+    } else {
+        args.push("--entry".to_string());
+        args.push(format!(
+            "--text={}",
+            options.description.clone().unwrap_or_default()
+        ));
+        if let Some(default_value) = &options.default_value {
+            args.push(format!("--entry-text={}", default_value));
+        }
+        if options.password {
+            args.push("--hide-text".to_string());
+        }
+    }
+    if let Some(title) = &options.title {
+        args.push(format!("--title={}", title));
+    }
+    if let Some(window) = x11_window(options.parent.as_ref()) {
+        args.push(format!("--attach={}", window));
+    }
+    return args;
+}
+
+fn kdialog_input_args(options: &InputOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(title) = &options.title {
+        args.push("--title".to_string());
+        args.push(title.clone());
+    }
+    if let Some(window) = x11_window(options.parent.as_ref()) {
+        args.push("--attach".to_string());
+        args.push(window);
+    }
+    let kind = match (options.password, options.multiline) {
+        (true, _) => "--password",
+        (false, true) => "--textinputbox",
+        (false, false) => "--inputbox",
+    };
+    args.push(kind.to_string());
+    args.push(options.description.clone().unwrap_or_default());
+    // `--password` has no way to pre-fill the input.
+    if let Some(default_value) = options.default_value.as_ref().filter(|_| !options.password) {
+        args.push(default_value.clone());
+    }
+    return args;
+}
+
+/// Shows a text input dialog with `zenity` or `kdialog`. Both print the text
+/// and exit with status 1 when the user cancels.
+pub(crate) fn input_dialog(
+    tool: Tool,
+    options: &InputOptions,
+    abort: &AbortToken,
+) -> RfdResult<Option<String>> {
+    let (args, input) = match tool {
+        Tool::Zenity if options.multiline && !options.password => {
+            let input = options.default_value.clone().unwrap_or_default();
+            (zenity_input_args(options), input.into_bytes())
+        }
+        Tool::Zenity => (zenity_input_args(options), Vec::new()),
+        Tool::Kdialog => (kdialog_input_args(options), Vec::new()),
+    };
+    let output = run_until(tool, &args, input, abort, None)?.unwrap();
+    match output.status {
+        0 => {}
+        1 => return Ok(None),
+        status => return Err(unexpected_status(tool, status)),
+    }
+    return Ok(Some(String::from_utf8_lossy(&output.stdout).into_owned()));
+}
+
+/// Parses zenity's `rgb(r,g,b)` or `rgba(r,g,b,a)` output, where `a` is a
+/// fraction from 0 to 1.
+fn color_from_css(css: &str) -> Option<Rgba> {
+    let inner = css
+        .strip_prefix("rgba(")
+        .or_else(|| css.strip_prefix("rgb("))?
+        .strip_suffix(')')?;
+    let parts: Vec<&str> = inner.split(',').map(|x| x.trim()).collect();
+    if parts.len() != 3 && parts.len() != 4 {
+        return None;
+    }
+    let mut rgba = [0xff; 4];
+    for i in 0..3 {
+        rgba[i] = parts[i].parse().ok()?;
+    }
+    if let Some(a) = parts.get(3) {
+        let a: f64 = a.parse().ok()?;
+        rgba[3] = (a.clamp(0.0, 1.0) * 255.0).round() as u8;
+    }
+    return Some(Rgba(rgba));
+}
+
+fn zenity_color_args(options: &ColorOptions) -> Vec<String> {
+    let mut args = vec!["--color-selection".to_string()];
+    if let Some(Rgba([r, g, b, a])) = options.color {
+        args.push(format!(
+            "--color=rgba({},{},{},{})",
+            r,
+            g,
+            b,
+            f64::from(a) / 255.0
+        ));
+    }
+    if let Some(title) = &options.title {
+        args.push(format!("--title={}", title));
+    }
+    if let Some(window) = x11_window(options.parent.as_ref()) {
+        args.push(format!("--attach={}", window));
+    }
+    return args;
+}
+
+fn kdialog_color_args(options: &ColorOptions) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(title) = &options.title {
+        args.push("--title".to_string());
+        args.push(title.clone());
+    }
+    if let Some(window) = x11_window(options.parent.as_ref()) {
+        args.push("--attach".to_string());
+        args.push(window);
+    }
+    args.push("--getcolor".to_string());
+    if let Some(color) = options.color {
+        args.push("--default".to_string());
+        args.push(color.to_hex(false));
+    }
+    return args;
+}
+
+/// Shows a color picker with `zenity` or `kdialog`. zenity prints a CSS
+/// `rgb()` color and kdialog a hex color.
+pub(crate) fn color_dialog(
+    tool: Tool,
+    options: &ColorOptions,
+    abort: &AbortToken,
+) -> RfdResult<Option<Rgba>> {
+    let args = match tool {
+        Tool::Zenity => zenity_color_args(options),
+        Tool::Kdialog => kdialog_color_args(options),
+    };
+    let output = run(tool, &args, abort)?;
+    match output.status {
+        0 => {}
+        1 => return Ok(None),
+        status => return Err(unexpected_status(tool, status)),
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let color = match tool {
+        Tool::Zenity => color_from_css(&stdout),
+        Tool::Kdialog => Rgba::from_hex(&stdout).ok(),
+    };
+    if color.is_none() {
+        return Err(RfdError::new(
+            ErrorCode::BackendUnavailable,
+            format!("{} printed an unexpected color: {}", tool.program(), stdout),
+        ));
+    }
+    return Ok(color);
+}
@@ -0,0 +1,81 @@
+use super::error::*;
+use std::fs;
+use std::path::Path;
+
+/// The text encodings that `FileHandle.text()` and `FileHandle.lines()` can
+/// decode. `latin1` maps every byte to the code point with the same number,
+/// like `Buffer.toString('latin1')` does.
+#[napi(string_enum)]
+#[derive(PartialEq)]
+pub enum TextEncoding {
+    #[napi(value = "utf-8")]
+    Utf8,
+    #[napi(value = "utf-16le")]
+    Utf16Le,
+    #[napi(value = "utf-16be")]
+    Utf16Be,
+    #[napi(value = "latin1")]
+    Latin1,
+}
+
+/// Options for `FileHandle.text()` and `FileHandle.lines()`. Without an
+/// `encoding`, it's picked by the byte order mark at the start of the file and
+/// defaults to UTF-8.
+#[napi(object)]
+pub struct TextOptions {
+    pub encoding: Option<TextEncoding>,
+}
+
+/// Picks the encoding from the byte order mark unless one was given. Returns
+/// it along with the length of the byte order mark to skip, which is 0 if
+/// there is none or it doesn't match the given encoding.
+pub(crate) fn detect(head: &[u8], encoding: Option<TextEncoding>) -> (TextEncoding, usize) {
+    let bom = if head.starts_with(b"\xef\xbb\xbf") {
+        Some((TextEncoding::Utf8, 3))
+    } else if head.starts_with(b"\xff\xfe") {
+        Some((TextEncoding::Utf16Le, 2))
+    } else if head.starts_with(b"\xfe\xff") {
+        Some((TextEncoding::Utf16Be, 2))
+    } else {
+        None
+    };
+    return match (encoding, bom) {
+        (None, Some(bom)) => bom,
+        (None, None) => (TextEncoding::Utf8, 0),
+        (Some(x), Some((y, len))) if x == y => (x, len),
+        (Some(x), _) => (x, 0),
+    };
+}
+
+/// Decodes the bytes, replacing invalid sequences with U+FFFD like
+/// `TextDecoder` does. A trailing odd byte of UTF-16 is replaced too.
+pub(crate) fn decode(bytes: &[u8], encoding: TextEncoding) -> String {
+    return match encoding {
+        TextEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        TextEncoding::Latin1 => bytes.iter().map(|x| char::from(*x)).collect(),
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+            let units = bytes.chunks_exact(2).map(|x| match encoding {
+                TextEncoding::Utf16Le => u16::from_le_bytes([x[0], x[1]]),
+                _ => u16::from_be_bytes([x[0], x[1]]),
+            });
+            let mut text: String = char::decode_utf16(units)
+                .map(|x| x.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect();
+            if bytes.len() % 2 == 1 {
+                text.push(char::REPLACEMENT_CHARACTER);
+            }
+            text
+        }
+    };
+}
+
+/// Reads and decodes the whole file at `path`.
+pub(crate) fn read_text(path: &Path, encoding: Option<TextEncoding>) -> RfdResult<String> {
+    let bytes = fs::read(path);
+    if bytes.is_err() {
+        return Err(RfdError::io(bytes.unwrap_err(), "read", path));
+    }
+    let bytes = bytes.unwrap();
+    let (encoding, bom) = detect(&bytes, encoding);
+    return Ok(decode(&bytes[bom..], encoding));
+}
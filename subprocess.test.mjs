@@ -0,0 +1,389 @@
+import test from "node:test";
+import assert from "node:assert";
+import {
+  chmodSync,
+  existsSync,
+  mkdtempSync,
+  readFileSync,
+  rmSync,
+  writeFileSync,
+} from "node:fs";
+import { tmpdir } from "node:os";
+import { delimiter, join } from "node:path";
+import {
+  AsyncFileDialog,
+  AsyncInputDialog,
+  AsyncMessageDialog,
+  ColorDialog,
+  ErrorCode,
+  FileDialog,
+  InputDialog,
+  MessageDialog,
+} from "./index.js";
+
+// Stand-ins for zenity and kdialog that record their arguments (one per line)
+// and answer with $STUB_STDOUT and $STUB_STATUS. With $STUB_SLEEP they record
+// their pid and hang instead, like a dialog that's still open. With $STUB_CAT
+// they echo their stdin first.
+const bin = mkdtempSync(join(tmpdir(), "rfd-subprocess-"));
+const argsFile = join(bin, "args");
+const pidFile = join(bin, "pid");
+for (const name of ["zenity", "kdialog"]) {
+  writeFileSync(
+    join(bin, name),
+    `#!/bin/sh
+printf '%s\\n' "$@" > "${argsFile}"
+if [ -n "$STUB_SLEEP" ]; then
+  echo $$ > "${pidFile}"
+  exec sleep "$STUB_SLEEP"
+fi
+if [ -n "$STUB_CAT" ]; then
+  cat
+fi
+printf '%s' "$STUB_STDOUT"
+exit "\${STUB_STATUS:-0}"
+`,
+  );
+  chmodSync(join(bin, name), 0o755);
+}
+
+function stub(stdout, status = 0) {
+  process.env.STUB_STDOUT = stdout;
+  process.env.STUB_STATUS = `${status}`;
+}
+
+function args() {
+  return readFileSync(argsFile, "utf8").split("\n").slice(0, -1);
+}
+
+const skip = process.platform !== "linux";
+const originalPath = process.env.PATH;
+
+test.beforeEach(() => {
+  process.env.PATH = bin + delimiter + originalPath;
+  stub("");
+});
+
+test.afterEach(() => {
+  process.env.PATH = originalPath;
+  delete process.env.RFD_BACKEND;
+  delete process.env.STUB_SLEEP;
+  delete process.env.STUB_CAT;
+});
+
+test("zenity gets the file dialog options as flags", { skip }, () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("/tmp/hello.txt\n");
+  const path = new FileDialog()
+    .addFilter("Text", ["txt", "md"])
+    .setDirectory("/tmp")
+    .setFileName("hello.txt")
+    .setTitle("Open")
+    .pickFile();
+  assert.equal(path, "/tmp/hello.txt");
+  assert.deepEqual(args(), [
+    "--file-selection",
+    "--separator=",
+    "",
+    "--title=Open",
+    "--filename=/tmp/hello.txt",
+    "--file-filter=Text | *.txt *.md",
+  ]);
+});
+
+test("zenity returns every picked file", { skip }, async () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("/tmp/a.txt\n/tmp/b.txt\n");
+  const fileHandles = await new AsyncFileDialog()
+    .setDirectory("/tmp")
+    .pickFiles();
+  assert.deepEqual(
+    fileHandles.map((x) => x.path()),
+    ["/tmp/a.txt", "/tmp/b.txt"],
+  );
+  assert.deepEqual(args().slice(0, 2), ["--file-selection", "--multiple"]);
+  assert.ok(args().includes("--filename=/tmp/"));
+});
+
+test("zenity saveFile() asks before overwriting", { skip }, () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("/tmp/out.txt\n");
+  assert.equal(new FileDialog().saveFile(), "/tmp/out.txt");
+  assert.deepEqual(args().slice(0, 3), [
+    "--file-selection",
+    "--save",
+    "--confirm-overwrite",
+  ]);
+});
+
+test("saveFiles() picks a folder for the files", { skip }, async () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("/home/user\n");
+  const fileHandles = await new AsyncFileDialog().saveFiles(["a.txt", "b.txt"]);
+  assert.deepEqual(
+    fileHandles.map((x) => x.path()),
+    ["/home/user/a.txt", "/home/user/b.txt"],
+  );
+  assert.deepEqual(args().slice(0, 2), ["--file-selection", "--directory"]);
+});
+
+test("exit status 1 means the dialog was cancelled", { skip }, async () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("", 1);
+  assert.equal(new FileDialog().pickFile(), undefined);
+  assert.equal(await new AsyncFileDialog().pickFolder(), undefined);
+});
+
+test("other exit statuses are ERR_BACKEND_UNAVAILABLE", { skip }, () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("", 5);
+  assert.throws(() => new FileDialog().pickFile(), {
+    code: ErrorCode.BackendUnavailable,
+  });
+});
+
+test("a missing binary is ERR_BACKEND_UNAVAILABLE", { skip }, async () => {
+  process.env.RFD_BACKEND = "kdialog";
+  process.env.PATH = mkdtempSync(join(tmpdir(), "rfd-empty-"));
+  await assert.rejects(new AsyncFileDialog().pickFile(), {
+    code: ErrorCode.BackendUnavailable,
+    message: /kdialog is not installed/,
+  });
+});
+
+test("kdialog gets the file dialog options as arguments", { skip }, () => {
+  process.env.RFD_BACKEND = "kdialog";
+  stub("/tmp/a.png\n/tmp/b.jpg\n");
+  const paths = new FileDialog()
+    .addFilter("Images", ["png", "jpg"])
+    .addFilter("All Files", ["*"])
+    .setDirectory("/tmp")
+    .setTitle("Pick images")
+    .pickFiles();
+  assert.deepEqual(paths, ["/tmp/a.png", "/tmp/b.jpg"]);
+  assert.deepEqual(args(), [
+    "--title",
+    "Pick images",
+    "--getopenfilename",
+    "--multiple",
+    "--separate-output",
+    "/tmp/",
+    "Images (*.png *.jpg)",
+    "All Files (*)",
+  ]);
+});
+
+test("kdialog can't pick multiple folders", { skip }, () => {
+  process.env.RFD_BACKEND = "kdialog";
+  assert.throws(() => new FileDialog().pickFolders(), {
+    code: ErrorCode.BackendUnavailable,
+  });
+});
+
+test("zenity exit statuses are mapped to buttons", { skip }, async () => {
+  process.env.RFD_BACKEND = "zenity";
+  const dialog = new AsyncMessageDialog()
+    .setTitle("Hello")
+    .setDescription("Are you sure?")
+    .setButtons("YesNo");
+  assert.equal(await dialog.show(), "Yes");
+  assert.deepEqual(args(), [
+    "--question",
+    "--title=Hello",
+    "--text=Are you sure?",
+  ]);
+  stub("", 1);
+  assert.equal(await dialog.show(), "No");
+});
+
+test("zenity prints the extra button's label", { skip }, () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("Don't Save\n", 1);
+  const dialog = new MessageDialog()
+    .setLevel("Warning")
+    .setButtons("YesNoCancelCustom", ["Save", "Don't Save", "Cancel"]);
+  assert.equal(dialog.show(), "Don't Save");
+  assert.deepEqual(args(), [
+    "--question",
+    "--ok-label=Save",
+    "--cancel-label=Cancel",
+    "--extra-button=Don't Save",
+    "--text=",
+  ]);
+  stub("");
+  assert.equal(dialog.show(), "Save");
+});
+
+test("pressing Esc in a zenity YesNoCancel dialog cancels it", { skip }, () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("", 1);
+  const dialog = new MessageDialog().setButtons("YesNoCancel");
+  assert.equal(dialog.show(), "Cancel");
+  assert.deepEqual(args(), [
+    "--question",
+    "--ok-label=Yes",
+    "--cancel-label=Cancel",
+    "--extra-button=No",
+    "--text=",
+  ]);
+  stub("No\n", 1);
+  assert.equal(dialog.show(), "No");
+  stub("", 1);
+  const custom = new MessageDialog().setButtons("YesNoCancelCustom", [
+    "Save",
+    "Discard",
+    "Back",
+  ]);
+  assert.equal(custom.show(), "Back");
+});
+
+test("zenity uses the level for plain Ok dialogs", { skip }, () => {
+  process.env.RFD_BACKEND = "zenity";
+  new MessageDialog().setLevel("Error").setDescription("Oops").show();
+  assert.deepEqual(args(), ["--error", "--text=Oops"]);
+});
+
+test("kdialog exit statuses are mapped to buttons", { skip }, () => {
+  process.env.RFD_BACKEND = "kdialog";
+  const dialog = new MessageDialog()
+    .setDescription("Save changes?")
+    .setButtons("YesNoCancel");
+  assert.equal(dialog.show(), "Yes");
+  assert.deepEqual(args(), ["--yesnocancel", "Save changes?"]);
+  stub("", 1);
+  assert.equal(dialog.show(), "No");
+  stub("", 2);
+  assert.equal(dialog.show(), "Cancel");
+});
+
+test("kdialog passes custom labels", { skip }, () => {
+  process.env.RFD_BACKEND = "kdialog";
+  stub("", 2);
+  const result = new MessageDialog()
+    .setLevel("Warning")
+    .setButtons("YesNoCancelCustom", ["Save", "Discard", "Back"])
+    .show();
+  assert.equal(result, "Back");
+  assert.deepEqual(args(), [
+    "--warningyesnocancel",
+    "",
+    "--yes-label",
+    "Save",
+    "--no-label",
+    "Discard",
+    "--cancel-label",
+    "Back",
+  ]);
+});
+
+test("aborting the signal kills the dialog", { skip }, async () => {
+  process.env.RFD_BACKEND = "zenity";
+  process.env.STUB_SLEEP = "30";
+  rmSync(pidFile, { force: true });
+  const controller = new AbortController();
+  const answer = new AsyncMessageDialog().show({ signal: controller.signal });
+  while (!existsSync(pidFile) || !readFileSync(pidFile, "utf8")) {
+    await new Promise((resolve) => setTimeout(resolve, 10));
+  }
+  const pid = Number(readFileSync(pidFile, "utf8"));
+  controller.abort();
+  await assert.rejects(answer, {
+    name: "AbortError",
+    code: ErrorCode.Cancelled,
+  });
+  assert.throws(() => process.kill(pid, 0), { code: "ESRCH" });
+});
+
+test("the dialog is killed when the timeout runs out", { skip }, async () => {
+  process.env.RFD_BACKEND = "kdialog";
+  process.env.STUB_SLEEP = "30";
+  const response = await new AsyncMessageDialog()
+    .setButtons("YesNo")
+    .setTimeout(100, "No")
+    .showDetailed();
+  assert.deepEqual(response, { result: "No", timedOut: true });
+  const pid = Number(readFileSync(pidFile, "utf8"));
+  assert.throws(() => process.kill(pid, 0), { code: "ESRCH" });
+  process.env.STUB_SLEEP = "";
+  stub("", 0);
+  assert.equal(new MessageDialog().setTimeout(5000, "Ok").show(), "Ok");
+});
+
+test("zenity shows input dialogs with --entry", { skip }, async () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("Photos\n");
+  const name = await new AsyncInputDialog()
+    .setTitle("New folder")
+    .setDescription("Name:")
+    .setDefaultValue("Untitled")
+    .show();
+  assert.equal(name, "Photos");
+  assert.deepEqual(args(), [
+    "--entry",
+    "--text=Name:",
+    "--entry-text=Untitled",
+    "--title=New folder",
+  ]);
+  stub("", 1);
+  assert.equal(new InputDialog().setPassword(true).show(), null);
+  assert.deepEqual(args(), ["--entry", "--text=", "--hide-text"]);
+});
+
+test("zenity gets the multiline default value on stdin", { skip }, () => {
+  process.env.RFD_BACKEND = "zenity";
+  process.env.STUB_CAT = "1";
+  const text = new InputDialog()
+    .setMultiline(true)
+    .setDefaultValue("line 1\nline 2")
+    .show();
+  assert.equal(text, "line 1\nline 2");
+  assert.deepEqual(args(), ["--text-info", "--editable"]);
+});
+
+test("kdialog shows input dialogs", { skip }, () => {
+  process.env.RFD_BACKEND = "kdialog";
+  stub("hunter2\n");
+  const password = new InputDialog()
+    .setDescription("Passphrase:")
+    .setDefaultValue("ignored")
+    .setPassword(true)
+    .show();
+  assert.equal(password, "hunter2");
+  assert.deepEqual(args(), ["--password", "Passphrase:"]);
+  new InputDialog().setMultiline(true).setDefaultValue("Hi").show();
+  assert.deepEqual(args(), ["--textinputbox", "", "Hi"]);
+});
+
+test("zenity shows color dialogs", { skip }, () => {
+  process.env.RFD_BACKEND = "zenity";
+  stub("rgba(255,136,0,0.5)\n");
+  const color = new ColorDialog()
+    .setTitle("Highlight")
+    .setColor("#ff880080")
+    .setAlpha(true)
+    .show();
+  assert.deepEqual(color, {
+    r: 255,
+    g: 136,
+    b: 0,
+    a: 128 / 255,
+    hex: "#ff880080",
+  });
+  assert.deepEqual(args(), [
+    "--color-selection",
+    `--color=rgba(255,136,0,${128 / 255})`,
+    "--title=Highlight",
+  ]);
+  stub("rgb(1,2,3)\n");
+  assert.equal(new ColorDialog().show().hex, "#010203");
+});
+
+test("kdialog shows color dialogs", { skip }, () => {
+  process.env.RFD_BACKEND = "kdialog";
+  stub("#00ff00\n");
+  const color = new ColorDialog().setColor("#f80").show();
+  assert.deepEqual(color, { r: 0, g: 255, b: 0, a: 1, hex: "#00ff00" });
+  assert.deepEqual(args(), ["--getcolor", "--default", "#ff8800"]);
+  stub("", 1);
+  assert.equal(new ColorDialog().show(), null);
+});